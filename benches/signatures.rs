@@ -0,0 +1,212 @@
+use blsttc_benchmark::config::{parse_node_counts, Config};
+use blsttc_benchmark::ecdsa::{EcdsaP256, EcdsaSecp256k1};
+use blsttc_benchmark::ed25519::Ed25519;
+use blsttc_benchmark::frost::FrostEd25519;
+use blsttc_benchmark::multisig_bls::{MultisigBlsG1, MultisigBlsG2};
+use blsttc_benchmark::multisig_bls_nizk::NizkMultisigBlsG1;
+use blsttc_benchmark::multisig_bls_pop::{MultisigBlsPopG1, MultisigBlsPopG2};
+use blsttc_benchmark::musig2::MuSig2;
+use blsttc_benchmark::rng::random_message;
+use blsttc_benchmark::schnorr::Bip340Schnorr;
+use blsttc_benchmark::simple_bls::{SimpleBlsG1, SimpleBlsG2};
+use blsttc_benchmark::threshold_bls::{ThresholdBlsG1, ThresholdBlsG2};
+use blsttc_benchmark::{fplus1, SignatureScheme, DEFAULT_MESSAGE_SIZE};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::env;
+
+//node counts can be overridden with e.g. `BENCH_NODES=10,100..500:100 cargo bench`
+fn node_counts() -> Vec<usize> {
+    match env::var("BENCH_NODES") {
+        Ok(nodes) => nodes
+            .split(',')
+            .flat_map(|n| parse_node_counts(n).unwrap().0)
+            .collect(),
+        Err(_) => vec![10, 50, 100],
+    }
+}
+
+//benchmarks every operation of the scheme built by `new_scheme` as one
+//criterion group, with the node count as parameter
+fn bench_scheme<S: SignatureScheme>(
+    c: &mut Criterion,
+    name: &str,
+    mut new_scheme: impl FnMut(usize) -> S,
+) {
+    let msg = &random_message(0, DEFAULT_MESSAGE_SIZE)[..];
+    let mut group = c.benchmark_group(name);
+
+    for nodes in node_counts() {
+        let mut scheme = new_scheme(nodes);
+        let (mut pks, sks): (Vec<_>, Vec<_>) = scheme.keygen(nodes).into_iter().unzip();
+        //proofs are checked here, before any bench runs, so the keys
+        //aggregated below are proven whichever benches are filtered out
+        let proves_possession = pks
+            .iter_mut()
+            .zip(&sks)
+            .all(|(pk, sk)| scheme.prove_possession(pk, sk));
+        if proves_possession {
+            assert!(
+                pks.iter_mut().all(|pk| scheme.verify_possession(pk)),
+                "{}: proofs of possession don't verify",
+                name
+            );
+            group.throughput(Throughput::Elements(nodes as u64));
+            group.bench_function(BenchmarkId::new("prove_possession", nodes), |b| {
+                b.iter(|| {
+                    for (pk, sk) in pks.iter_mut().zip(&sks) {
+                        scheme.prove_possession(black_box(pk), black_box(sk));
+                    }
+                })
+            });
+            group.bench_function(BenchmarkId::new("verify_possession", nodes), |b| {
+                b.iter(|| {
+                    for pk in pks.iter_mut() {
+                        scheme.verify_possession(black_box(pk));
+                    }
+                })
+            });
+        }
+        //the last nonces committed are the ones the signs below use
+        if scheme.commit_nonces() {
+            group.throughput(Throughput::Elements(nodes as u64));
+            group.bench_function(BenchmarkId::new("commit_nonces", nodes), |b| {
+                b.iter(|| scheme.commit_nonces())
+            });
+        }
+        let signs: Vec<_> = sks.iter().map(|sk| scheme.sign(sk, msg)).collect();
+        let (pk, sk) = (&pks[0], &sks[0]);
+
+        group.throughput(Throughput::Elements(1));
+        if scheme.hash_message(msg).is_some() {
+            group.bench_function(BenchmarkId::new("hash_message", nodes), |b| {
+                b.iter(|| scheme.hash_message(black_box(msg)))
+            });
+        }
+        group.bench_function(BenchmarkId::new("sign", nodes), |b| {
+            b.iter(|| scheme.sign(black_box(sk), black_box(msg)))
+        });
+        group.bench_function(BenchmarkId::new("verify", nodes), |b| {
+            b.iter(|| scheme.verify(black_box(pk), black_box(&signs[0]), black_box(msg)))
+        });
+
+        let verify_all = |count: usize| {
+            for (pk, sign) in pks.iter().zip(&signs).take(count) {
+                scheme.verify(black_box(pk), black_box(sign), black_box(msg));
+            }
+        };
+        group.throughput(Throughput::Elements(fplus1(nodes) as u64));
+        group.bench_function(BenchmarkId::new("verify_fplus1", nodes), |b| {
+            b.iter(|| verify_all(fplus1(nodes)))
+        });
+        group.throughput(Throughput::Elements(nodes as u64));
+        group.bench_function(BenchmarkId::new("verify_n", nodes), |b| {
+            b.iter(|| verify_all(nodes))
+        });
+        if scheme.verify_batch(&pks, &signs, msg).is_some() {
+            group.bench_function(BenchmarkId::new("verify_n_batch", nodes), |b| {
+                b.iter(|| scheme.verify_batch(black_box(&pks), black_box(&signs), black_box(msg)))
+            });
+        }
+
+        let agg_pk = scheme.aggregate_public_keys(&pks);
+        if agg_pk.is_some() {
+            group.bench_function(BenchmarkId::new("aggregate_pubkey", nodes), |b| {
+                b.iter(|| scheme.aggregate_public_keys(black_box(&pks)))
+            });
+        }
+        if scheme.aggregate_public_keys_parallel(&pks).is_some() {
+            group.bench_function(BenchmarkId::new("aggregate_pubkey_parallel", nodes), |b| {
+                b.iter(|| scheme.aggregate_public_keys_parallel(black_box(&pks)))
+            });
+        }
+
+        let indexed_signs: Vec<_> = signs.iter().cloned().enumerate().collect();
+        if let Some(agg_sign) = scheme.aggregate_signatures(&indexed_signs) {
+            group.bench_function(BenchmarkId::new("aggregate_sign", nodes), |b| {
+                b.iter(|| scheme.aggregate_signatures(black_box(&indexed_signs)))
+            });
+            if scheme
+                .aggregate_signatures_parallel(&indexed_signs)
+                .is_some()
+            {
+                group.bench_function(BenchmarkId::new("aggregate_sign_parallel", nodes), |b| {
+                    b.iter(|| scheme.aggregate_signatures_parallel(black_box(&indexed_signs)))
+                });
+            }
+            //a bench of a rejected aggregate would time the wrong path
+            if proves_possession {
+                assert!(agg_pk.is_some(), "{}: proven keys aren't aggregated", name);
+            }
+            assert!(
+                scheme.verify_aggregate(agg_pk.as_ref(), &agg_sign, msg),
+                "{}: aggregate doesn't verify",
+                name
+            );
+            group.throughput(Throughput::Elements(1));
+            group.bench_function(BenchmarkId::new("verify_aggregate", nodes), |b| {
+                b.iter(|| {
+                    scheme.verify_aggregate(
+                        black_box(agg_pk.as_ref()),
+                        black_box(&agg_sign),
+                        black_box(msg),
+                    )
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn simple_bls(c: &mut Criterion) {
+    bench_scheme(c, "simple_bls_g1", |_| SimpleBlsG1);
+    bench_scheme(c, "simple_bls_g2", |_| SimpleBlsG2);
+}
+
+fn threshold_bls(c: &mut Criterion) {
+    let config = Config::default();
+    bench_scheme(c, "threshold_bls_g1", |nodes| {
+        ThresholdBlsG1::new(config.threshold.threshold(nodes))
+    });
+    bench_scheme(c, "threshold_bls_g2", |nodes| {
+        ThresholdBlsG2::new(config.threshold.threshold(nodes))
+    });
+    bench_scheme(c, "frost_ed25519", |nodes| {
+        FrostEd25519::new(config.threshold.threshold(nodes))
+    });
+}
+
+fn multisig_bls(c: &mut Criterion) {
+    let threshold = Config::default().multisig_threshold;
+    bench_scheme(c, "multisig_bls_g1", |_| MultisigBlsG1 { threshold });
+    bench_scheme(c, "multisig_bls_g2", |_| MultisigBlsG2 { threshold });
+    bench_scheme(c, "multisig_bls_nizk_g1", |_| NizkMultisigBlsG1 {
+        threshold,
+    });
+    bench_scheme(c, "multisig_bls_pop_g1", |_| MultisigBlsPopG1);
+    bench_scheme(c, "multisig_bls_pop_g2", |_| MultisigBlsPopG2);
+}
+
+fn ed25519(c: &mut Criterion) {
+    bench_scheme(c, "ed25519", |_| Ed25519);
+}
+
+fn ecdsa(c: &mut Criterion) {
+    bench_scheme(c, "ecdsa_secp256k1", |_| EcdsaSecp256k1);
+    bench_scheme(c, "ecdsa_p256", |_| EcdsaP256);
+}
+
+fn schnorr(c: &mut Criterion) {
+    bench_scheme(c, "schnorr_bip340", |_| Bip340Schnorr);
+    bench_scheme(c, "musig2", |_| MuSig2::default());
+}
+
+criterion_group!(
+    benches,
+    simple_bls,
+    threshold_bls,
+    multisig_bls,
+    ed25519,
+    ecdsa,
+    schnorr
+);
+criterion_main!(benches);
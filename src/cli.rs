@@ -0,0 +1,135 @@
+use super::config::{parse_node_counts, Config, NodeCounts, Scheme};
+use super::output::OutputFormat;
+use super::Group;
+use clap::Parser;
+use std::path::PathBuf;
+
+//flags given on the command line override the values of the config file
+#[derive(Debug, Parser)]
+#[command(
+    about = "Benchmarks bls (simple, threshold, multisig, multisig with proof of possession), ed25519, ecdsa (secp256k1, p-256), bip-340 schnorr, musig2 and frost signatures"
+)]
+pub struct Cli {
+    /// TOML file describing the benchmark matrix, e.g. `profiles/smoke.toml`
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Schemes to benchmark [default: all]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub schemes: Option<Vec<Scheme>>,
+
+    /// Groups to sign in, for the bls schemes that support both (nizk is g1 only) [default: g1,g2]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub groups: Option<Vec<Group>>,
+
+    /// Comma separated node counts or inclusive ranges, e.g. `10,20,100..1000:100`
+    #[arg(long, value_delimiter = ',', value_parser = parse_node_counts)]
+    pub nodes: Option<Vec<NodeCounts>>,
+
+    /// Threshold of the key set the multisig shares are dealt from, less than every node count [default: 2]
+    #[arg(long)]
+    pub multisig_threshold: Option<usize>,
+
+    /// Comma separated sizes in bytes of the random payloads signed, e.g. `32,1024,262144` [default: 24]
+    #[arg(long, value_delimiter = ',')]
+    pub message_sizes: Option<Vec<usize>>,
+
+    /// Sign and verify a precomputed hash of the payload, timing hash-to-curve only on its own
+    #[arg(long)]
+    pub prehashed: bool,
+
+    /// Summarise timings on the fly instead of keeping every sample, for large committees; leaves out the median ci, trimmed mean, mad and outlier counts
+    #[arg(long)]
+    pub streaming: bool,
+
+    /// Also measure sustained ops/sec, running each operation in a closed loop for this many seconds
+    #[arg(long)]
+    pub throughput_secs: Option<f64>,
+
+    /// Threads of the multi-threaded throughput, and the most threads of the parallel verification [default: all cores]
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Also verify the n signs on worker pools of every size from 1 to `--threads`, reporting the speedup
+    #[arg(long)]
+    pub parallel_verify: bool,
+
+    /// Untimed runs of every operation before it is measured [default: 1]
+    #[arg(long)]
+    pub warmup: Option<usize>,
+
+    /// Timed runs of every operation, stats are computed over all of them [default: 1]
+    #[arg(long)]
+    pub iterations: Option<usize>,
+
+    /// Formats the results are written in, `hdr` writes an interval log of the latency histograms [default: csv]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub formats: Option<Vec<OutputFormat>>,
+
+    /// Directory the results are written to [default: .]
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+
+    /// Seed of the rng all keys and nonces are drawn from, to rerun a benchmark bit-for-bit [default: random]
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+impl Cli {
+    //loads the config file (or the defaults) and applies the flags on top
+    pub fn config(&self) -> Result<Config, String> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+
+        if let Some(schemes) = &self.schemes {
+            config.schemes = schemes.clone();
+        }
+        if let Some(groups) = &self.groups {
+            config.groups = groups.clone();
+        }
+        if let Some(nodes) = &self.nodes {
+            config.nodes = nodes.clone();
+        }
+        if let Some(multisig_threshold) = self.multisig_threshold {
+            config.multisig_threshold = multisig_threshold;
+        }
+        if let Some(message_sizes) = &self.message_sizes {
+            config.message_sizes = message_sizes.clone();
+        }
+        if self.prehashed {
+            config.prehashed = true;
+        }
+        if self.streaming {
+            config.streaming = true;
+        }
+        if let Some(secs) = self.throughput_secs {
+            config.throughput_secs = Some(secs);
+        }
+        if let Some(threads) = self.threads {
+            config.threads = Some(threads);
+        }
+        if self.parallel_verify {
+            config.parallel_verify = true;
+        }
+        if let Some(warmup) = self.warmup {
+            config.warmup = warmup;
+        }
+        if let Some(iterations) = self.iterations {
+            config.iterations = iterations;
+        }
+        if let Some(formats) = &self.formats {
+            config.formats = formats.clone();
+        }
+        if let Some(out_dir) = &self.out_dir {
+            config.out_dir = out_dir.clone();
+        }
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+        config.validate()?;
+
+        Ok(config)
+    }
+}
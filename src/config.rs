@@ -0,0 +1,467 @@
+use super::output::OutputFormat;
+use super::rng::{random_message, random_seed};
+use super::{BenchParams, Group, DEFAULT_MESSAGE_SIZE};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+//schemes that can be selected for a run
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scheme {
+    Simple,
+    Threshold,
+    Multisig,
+    Nizk,
+    //multisig from independent keys with proofs of possession
+    #[serde(rename = "multisig-pop")]
+    MultisigPop,
+    Ed25519,
+    Secp256k1,
+    P256,
+    Schnorr,
+    Musig2,
+    Frost,
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 11] = [
+        Scheme::Simple,
+        Scheme::Threshold,
+        Scheme::Multisig,
+        Scheme::Nizk,
+        Scheme::MultisigPop,
+        Scheme::Ed25519,
+        Scheme::Secp256k1,
+        Scheme::P256,
+        Scheme::Schnorr,
+        Scheme::Musig2,
+        Scheme::Frost,
+    ];
+
+    //name of the output files of the scheme, without extension
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            Scheme::Simple => "simple",
+            Scheme::Threshold => "threshold",
+            Scheme::Multisig => "multisig_aggregation",
+            Scheme::Nizk => "multisig_aggregation_with_nizk",
+            Scheme::MultisigPop => "multisig_aggregation_with_pop",
+            Scheme::Ed25519 => "ed25519",
+            Scheme::Secp256k1 => "ecdsa_secp256k1",
+            Scheme::P256 => "ecdsa_p256",
+            Scheme::Schnorr => "schnorr_bip340",
+            Scheme::Musig2 => "musig2",
+            Scheme::Frost => "frost_ed25519",
+        }
+    }
+}
+
+//node counts, either a single count or a range
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "NodeSpec")]
+pub struct NodeCounts(pub Vec<usize>);
+
+//node counts as written in a config file: `10` or `"100..1000:100"`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NodeSpec {
+    Count(usize),
+    Range(String),
+}
+
+impl TryFrom<NodeSpec> for NodeCounts {
+    type Error = String;
+
+    fn try_from(spec: NodeSpec) -> Result<Self, Self::Error> {
+        match spec {
+            NodeSpec::Count(n) => parse_node_counts(&n.to_string()),
+            NodeSpec::Range(s) => parse_node_counts(&s),
+        }
+    }
+}
+
+//parses `N` or the inclusive range `START..END[:STEP]`
+pub fn parse_node_counts(s: &str) -> Result<NodeCounts, String> {
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|e| format!("invalid node count `{}`: {}", n, e))
+    };
+
+    let counts = match s.split_once("..") {
+        None => vec![parse(s)?],
+        Some((start, rest)) => {
+            let (end, step) = match rest.split_once(':') {
+                None => (parse(rest)?, 1),
+                Some((end, step)) => (parse(end)?, parse(step)?),
+            };
+            let start = parse(start)?;
+            if step == 0 {
+                return Err(format!("step of `{}` must be greater than 0", s));
+            }
+            if start > end {
+                return Err(format!("range `{}` is empty", s));
+            }
+            (start..=end).step_by(step).collect()
+        }
+    };
+    if counts.contains(&0) {
+        return Err("node counts must be greater than 0".to_string());
+    }
+
+    Ok(NodeCounts(counts))
+}
+
+//how the threshold t of the threshold schemes follows the committee size;
+//written as `"one-third"` or as a fixed number in a config file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "ThresholdSpec")]
+pub enum ThresholdPolicy {
+    //t = (n - 1) / 3, the most faulty nodes a bft committee tolerates
+    OneThird,
+    Fixed(usize),
+}
+
+impl ThresholdPolicy {
+    pub fn threshold(&self, nodes: usize) -> usize {
+        match self {
+            ThresholdPolicy::OneThird => nodes.saturating_sub(1) / 3,
+            ThresholdPolicy::Fixed(threshold) => *threshold,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThresholdSpec {
+    Fixed(usize),
+    Named(String),
+}
+
+impl TryFrom<ThresholdSpec> for ThresholdPolicy {
+    type Error = String;
+
+    fn try_from(spec: ThresholdSpec) -> Result<Self, Self::Error> {
+        match spec {
+            ThresholdSpec::Fixed(threshold) => Ok(ThresholdPolicy::Fixed(threshold)),
+            ThresholdSpec::Named(name) if name == "one-third" => Ok(ThresholdPolicy::OneThird),
+            ThresholdSpec::Named(name) => Err(format!(
+                "unknown threshold `{}`, expected `one-third` or a number",
+                name
+            )),
+        }
+    }
+}
+
+//the whole benchmark matrix of a run; every key of the config file is
+//optional and falls back to the value in `Config::default`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub schemes: Vec<Scheme>,
+    pub groups: Vec<Group>,
+    pub nodes: Vec<NodeCounts>,
+    //threshold of the threshold bls and frost schemes
+    pub threshold: ThresholdPolicy,
+    //threshold of the key set the multisig shares are dealt from
+    pub multisig_threshold: usize,
+    //sizes in bytes of the random payloads signed, one sweep per size
+    pub message_sizes: Vec<usize>,
+    //sign and verify a precomputed hash of the payload
+    pub prehashed: bool,
+    //summarise the timings on the fly instead of keeping every sample, to
+    //bound memory at large node counts
+    pub streaming: bool,
+    //seconds each operation runs in a closed loop to measure its throughput,
+    //no throughput is measured when not given
+    pub throughput_secs: Option<f64>,
+    //threads of the multi-threaded throughput and the most threads of the
+    //parallel verification, all cores when not given
+    pub threads: Option<usize>,
+    //also verify the n signs on worker pools of 1 to `threads` threads
+    pub parallel_verify: bool,
+    //untimed runs of every operation before it is measured
+    pub warmup: usize,
+    //timed runs of every operation, stats are computed over all of them
+    pub iterations: usize,
+    pub formats: Vec<OutputFormat>,
+    pub out_dir: PathBuf,
+    //seed of the rng all keys and nonces are drawn from; a random one is
+    //picked (and recorded in the results) when not given
+    pub seed: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            schemes: Scheme::ALL.to_vec(),
+            groups: vec![Group::G1, Group::G2],
+            nodes: [10, 20, 50, 100, 150, 200, 250, 500, 1000]
+                .iter()
+                .map(|&n| NodeCounts(vec![n]))
+                .collect(),
+            threshold: ThresholdPolicy::OneThird,
+            multisig_threshold: 2, // some random value less than total nodes
+            message_sizes: vec![DEFAULT_MESSAGE_SIZE],
+            prehashed: false,
+            streaming: false,
+            throughput_secs: None,
+            threads: None,
+            parallel_verify: false,
+            warmup: 1,
+            iterations: 1,
+            formats: vec![OutputFormat::Csv],
+            out_dir: PathBuf::from("."),
+            seed: None,
+        }
+    }
+}
+
+impl Config {
+    //reads a config file written in toml
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read config `{}`: {}", path.display(), e))?;
+        let config: Config = toml::from_str(&contents)
+            .map_err(|e| format!("invalid config `{}`: {}", path.display(), e))?;
+
+        Ok(config)
+    }
+
+    //fixes the seed of the run, drawing a random one if none was given
+    pub fn resolve_seed(&mut self) -> u64 {
+        *self.seed.get_or_insert_with(random_seed)
+    }
+
+    //checks the settings that would otherwise fail in the middle of a run
+    pub fn validate(&self) -> Result<(), String> {
+        if self.iterations == 0 {
+            return Err("iterations must be greater than 0".to_string());
+        }
+        if self.node_counts().is_empty() {
+            return Err("no node counts given".to_string());
+        }
+        if self.message_sizes.is_empty() {
+            return Err("no message sizes given".to_string());
+        }
+        if let Some(secs) = self.throughput_secs {
+            if !(secs.is_finite() && secs > 0.0) {
+                return Err(format!("throughput duration {} must be positive", secs));
+            }
+            Duration::try_from_secs_f64(secs)
+                .map_err(|e| format!("throughput duration {} is invalid: {}", secs, e))?;
+        }
+        if self.threads == Some(0) {
+            return Err("threads must be greater than 0".to_string());
+        }
+        let threshold_schemes = [Scheme::Threshold, Scheme::Frost];
+        if let (true, ThresholdPolicy::Fixed(threshold)) = (
+            threshold_schemes
+                .iter()
+                .any(|scheme| self.schemes.contains(scheme)),
+            self.threshold,
+        ) {
+            if let Some(nodes) = self.node_counts().into_iter().find(|&n| n <= threshold) {
+                return Err(format!(
+                    "threshold {} needs more than {} nodes to combine",
+                    threshold, nodes
+                ));
+            }
+        }
+        let multisig_schemes = [Scheme::Multisig, Scheme::Nizk];
+        if multisig_schemes
+            .iter()
+            .any(|scheme| self.schemes.contains(scheme))
+        {
+            if let Some(nodes) = self
+                .node_counts()
+                .into_iter()
+                .find(|&n| n <= self.multisig_threshold)
+            {
+                return Err(format!(
+                    "multisig threshold {} must be less than the {} nodes the shares are dealt to",
+                    self.multisig_threshold, nodes
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    //all node counts, in the order given
+    pub fn node_counts(&self) -> Vec<usize> {
+        self.nodes
+            .iter()
+            .flat_map(|n| n.0.iter().copied())
+            .collect()
+    }
+
+    //sweep settings for one message size
+    pub fn bench_params(&self, message_size: usize) -> BenchParams {
+        let seed = self.seed.unwrap_or_else(random_seed);
+        BenchParams {
+            message: random_message(seed, message_size),
+            warmup: self.warmup,
+            iterations: self.iterations,
+            seed,
+            prehashed: self.prehashed,
+            streaming: self.streaming,
+            //checked by `validate`
+            throughput: self
+                .throughput_secs
+                .map(|secs| Duration::try_from_secs_f64(secs).unwrap()),
+            threads: self.threads.unwrap_or_else(|| {
+                thread::available_parallelism().map_or(1, |threads| threads.get())
+            }),
+            parallel_verify: self.parallel_verify,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //a config running `schemes` on committees of `nodes`
+    fn config(schemes: &[Scheme], nodes: &[usize]) -> Config {
+        Config {
+            schemes: schemes.to_vec(),
+            nodes: nodes.iter().map(|&n| NodeCounts(vec![n])).collect(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn parses_counts_and_ranges() {
+        assert_eq!(parse_node_counts("10"), Ok(NodeCounts(vec![10])));
+        assert_eq!(
+            parse_node_counts("1..5"),
+            Ok(NodeCounts(vec![1, 2, 3, 4, 5]))
+        );
+        assert_eq!(
+            parse_node_counts("100..1000:300"),
+            Ok(NodeCounts(vec![100, 400, 700, 1000]))
+        );
+        //the end is only included when the step lands on it
+        assert_eq!(
+            parse_node_counts("1000..5000:1500"),
+            Ok(NodeCounts(vec![1000, 2500, 4000]))
+        );
+        assert_eq!(parse_node_counts("7..7"), Ok(NodeCounts(vec![7])));
+    }
+
+    #[test]
+    fn rejects_invalid_counts() {
+        assert!(parse_node_counts("0").is_err());
+        assert!(parse_node_counts("0..10").is_err());
+        assert!(parse_node_counts("1..5:0").is_err());
+        assert!(parse_node_counts("5..1").is_err());
+        assert!(parse_node_counts("ten").is_err());
+        assert!(parse_node_counts("1..").is_err());
+        assert!(parse_node_counts("-1").is_err());
+    }
+
+    #[test]
+    fn checks_threshold_against_nodes() {
+        let mut fixed = config(&[Scheme::Threshold], &[3, 10]);
+        fixed.threshold = ThresholdPolicy::Fixed(3);
+        assert!(fixed.validate().is_err());
+        fixed.threshold = ThresholdPolicy::Fixed(2);
+        assert!(fixed.validate().is_ok());
+
+        //only the threshold schemes care about t
+        let mut other = config(&[Scheme::Simple, Scheme::Frost], &[3]);
+        other.threshold = ThresholdPolicy::Fixed(3);
+        assert!(other.validate().is_err());
+        other.schemes = vec![Scheme::Simple];
+        assert!(other.validate().is_ok());
+
+        assert!(config(&[Scheme::Threshold], &[1]).validate().is_ok());
+    }
+
+    #[test]
+    fn checks_multisig_threshold_against_nodes() {
+        for scheme in [Scheme::Multisig, Scheme::Nizk] {
+            assert!(config(&[scheme], &[2, 10]).validate().is_err());
+            assert!(config(&[scheme], &[3, 10]).validate().is_ok());
+        }
+        assert!(config(&[Scheme::MultisigPop], &[2]).validate().is_ok());
+    }
+
+    #[test]
+    fn checks_run_settings() {
+        let valid = config(&[Scheme::Simple], &[10]);
+        assert!(valid.validate().is_ok());
+        for invalid in [
+            Config {
+                iterations: 0,
+                ..valid.clone()
+            },
+            Config {
+                nodes: vec![],
+                ..valid.clone()
+            },
+            Config {
+                message_sizes: vec![],
+                ..valid.clone()
+            },
+            Config {
+                throughput_secs: Some(0.0),
+                ..valid.clone()
+            },
+            Config {
+                throughput_secs: Some(f64::NAN),
+                ..valid.clone()
+            },
+            Config {
+                throughput_secs: Some(1e30),
+                ..valid.clone()
+            },
+            Config {
+                threads: Some(0),
+                ..valid.clone()
+            },
+        ] {
+            assert!(invalid.validate().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn loads_profiles() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("profiles");
+        let mut profiles: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        profiles.sort();
+        assert!(!profiles.is_empty());
+        for path in profiles {
+            let config = Config::load(&path).unwrap();
+            assert!(config.validate().is_ok(), "{}", path.display());
+        }
+
+        let aggregation = Config::load(&dir.join("aggregation.toml")).unwrap();
+        assert_eq!(aggregation.node_counts(), [1000, 2000, 3000, 4000, 5000]);
+        let smoke = Config::load(&dir.join("smoke.toml")).unwrap();
+        assert_eq!(smoke.schemes, Scheme::ALL);
+        assert_eq!(smoke.threshold, ThresholdPolicy::OneThird);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let parse = |contents: &str| toml::from_str::<Config>(contents);
+        assert!(parse("nodes = [\"5..1\"]").is_err());
+        assert!(parse("threshold = \"half\"").is_err());
+        assert!(parse("iteration = 3").is_err());
+        assert_eq!(
+            parse("threshold = 4").unwrap().threshold,
+            ThresholdPolicy::Fixed(4)
+        );
+        assert!(Config::load(Path::new("no/such/profile.toml")).is_err());
+    }
+}
@@ -1,140 +1,231 @@
-use ed25519_dalek as dalek;
-use ed25519_dalek::Signer as _;
-use rand::rngs::OsRng;
-use super::stats::{calculate_mean, calculate_median};
-use std::time::Instant;
-
-
-pub fn benchmark_ecdsa_signs(nodes: usize) -> (f64, f64, f64) {
-    // for normal sign-verify
-    let mut csprng = OsRng;
-    
-    let mut keypairs: Vec<_> = Vec::new();
-    let msg = "hello, this is benchmark".as_bytes();
-    let mut signs = Vec::new();
-
-    for _ in 0..nodes {
-        let keypair = dalek::Keypair::generate(&mut csprng);
-        let public = keypair.public;
-        let secret = keypair;
-        keypairs.push((public, secret));
+use super::report::{CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::{benchmark_scheme, BenchError, BenchParams, SignatureScheme};
+use k256::ecdsa as secp256k1;
+use k256::ecdsa::signature::hazmat::{PrehashSigner as _, PrehashVerifier as _};
+use k256::ecdsa::signature::{Signer as _, Verifier as _};
+use p256::ecdsa as p256;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+//ecdsa over secp256k1 with sha-256, as used by bitcoin-style chains; signs
+//carry their recovery id so the pubkey can be recovered from them
+pub struct EcdsaSecp256k1;
+
+impl SignatureScheme for EcdsaSecp256k1 {
+    type PublicKey = secp256k1::VerifyingKey;
+    type SecretKey = secp256k1::SigningKey;
+    type Signature = (secp256k1::Signature, secp256k1::RecoveryId);
+    type AggregatePublicKey = ();
+    type AggregateSignature = ();
+    //sha-256 digest of the message
+    type MessageHash = [u8; 32];
+
+    fn name(&self) -> &'static str {
+        "ecdsa secp256k1 sign"
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for creating single sign
-    let mut time_to_create_normal_sign = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        let sign = keypairs[i].1.sign(msg);
-
-        let end_time = Instant::now();
-
-        signs.push(sign);
-
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_normal_sign.push(elapsed_time.as_secs_f64());
+    fn keygen(&mut self, nodes: usize) -> Vec<(secp256k1::VerifyingKey, secp256k1::SigningKey)> {
+        (0..nodes)
+            .map(|_| {
+                let sk = with_rng(secp256k1::SigningKey::random);
+                (*sk.verifying_key(), sk)
+            })
+            .collect()
     }
 
+    //the nonce is derived from the key and message (rfc 6979)
+    fn sign(
+        &self,
+        sk: &secp256k1::SigningKey,
+        msg: &[u8],
+    ) -> (secp256k1::Signature, secp256k1::RecoveryId) {
+        sk.sign_recoverable(msg).unwrap()
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying single sign
-    let mut time_to_verify_normal_sign = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        keypairs[i].0.verify_strict(msg, &signs[i]).unwrap();
-
-        let end_time = Instant::now();
-
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_normal_sign.push(elapsed_time.as_secs_f64());
+    fn verify(
+        &self,
+        pk: &secp256k1::VerifyingKey,
+        sig: &(secp256k1::Signature, secp256k1::RecoveryId),
+        msg: &[u8],
+    ) -> bool {
+        pk.verify(msg, &sig.0).is_ok()
     }
 
+    fn hash_message(&self, msg: &[u8]) -> Option<[u8; 32]> {
+        Some(Sha256::digest(msg).into())
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn sign_hashed(
+        &self,
+        sk: &secp256k1::SigningKey,
+        hash: &[u8; 32],
+    ) -> Option<(secp256k1::Signature, secp256k1::RecoveryId)> {
+        Some(sk.sign_prehash_recoverable(hash).unwrap())
+    }
 
-    //for f+1 sign
-    let mut time_to_verify_fplus1_independent_signs = 0.0;
-    let n = (nodes - 1) / 3 + 1;
+    fn verify_hashed(
+        &self,
+        pk: &secp256k1::VerifyingKey,
+        sig: &(secp256k1::Signature, secp256k1::RecoveryId),
+        hash: &[u8; 32],
+    ) -> Option<bool> {
+        Some(pk.verify_prehash(hash, &sig.0).is_ok())
+    }
 
-    let start_time = Instant::now();
+    fn recover_public_key(
+        &self,
+        sig: &(secp256k1::Signature, secp256k1::RecoveryId),
+        msg: &[u8],
+    ) -> Option<secp256k1::VerifyingKey> {
+        secp256k1::VerifyingKey::recover_from_msg(msg, &sig.0, sig.1).ok()
+    }
 
-    for i in 0..n {
-        keypairs[i].1.verify_strict(msg, &signs[i]).unwrap();
+    //flips a bit of r
+    fn tampered_signature(
+        &self,
+        sig: &(secp256k1::Signature, secp256k1::RecoveryId),
+    ) -> Option<(secp256k1::Signature, secp256k1::RecoveryId)> {
+        let mut bytes = sig.0.to_bytes();
+        bytes[31] ^= 1;
+        let tampered = secp256k1::Signature::from_slice(&bytes).ok()?;
+        Some((tampered, sig.1))
     }
+}
 
-    let end_time = Instant::now();
+//ecdsa over nist p-256 with sha-256
+pub struct EcdsaP256;
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_fplus1_independent_signs = elapsed_time.as_secs_f64();
+impl SignatureScheme for EcdsaP256 {
+    type PublicKey = p256::VerifyingKey;
+    type SecretKey = p256::SigningKey;
+    type Signature = p256::Signature;
+    type AggregatePublicKey = ();
+    type AggregateSignature = ();
+    //sha-256 digest of the message
+    type MessageHash = [u8; 32];
 
+    fn name(&self) -> &'static str {
+        "ecdsa p-256 sign"
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn keygen(&mut self, nodes: usize) -> Vec<(p256::VerifyingKey, p256::SigningKey)> {
+        (0..nodes)
+            .map(|_| {
+                let sk = with_rng(p256::SigningKey::random);
+                (*sk.verifying_key(), sk)
+            })
+            .collect()
+    }
 
-    //for all sign
-    let mut time_to_verify_all_independent_signs = 0.0;
+    //the nonce is derived from the key and message (rfc 6979)
+    fn sign(&self, sk: &p256::SigningKey, msg: &[u8]) -> p256::Signature {
+        sk.sign(msg)
+    }
 
-    let start_time = Instant::now();
+    fn verify(&self, pk: &p256::VerifyingKey, sig: &p256::Signature, msg: &[u8]) -> bool {
+        pk.verify(msg, sig).is_ok()
+    }
 
-    for i in 0..nodes {
-        keypairs[i].1.verify_strict(msg,&signs[i]).unwrap();
+    fn hash_message(&self, msg: &[u8]) -> Option<[u8; 32]> {
+        Some(Sha256::digest(msg).into())
     }
 
-    let end_time = Instant::now();
+    fn sign_hashed(&self, sk: &p256::SigningKey, hash: &[u8; 32]) -> Option<p256::Signature> {
+        Some(sk.sign_prehash(hash).unwrap())
+    }
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs = elapsed_time.as_secs_f64();
+    fn verify_hashed(
+        &self,
+        pk: &p256::VerifyingKey,
+        sig: &p256::Signature,
+        hash: &[u8; 32],
+    ) -> Option<bool> {
+        Some(pk.verify_prehash(hash, sig).is_ok())
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    //flips a bit of r
+    fn tampered_signature(&self, sig: &p256::Signature) -> Option<p256::Signature> {
+        let mut bytes = sig.to_bytes();
+        bytes[31] ^= 1;
+        p256::Signature::from_slice(&bytes).ok()
+    }
+}
 
+//report of either ecdsa curve, written to a file per curve
+#[derive(Clone, Debug, Serialize)]
+pub struct EcdsaReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub ecdsa: SchemeReport,
+}
 
-    let mean_single_sign_creation = calculate_mean(&time_to_create_normal_sign) * 1000.0;
-    let median_single_sign_creation =
-        calculate_median(&mut time_to_create_normal_sign) * 1000.0;
-    let mean_single_sign_verify = calculate_mean(&time_to_verify_normal_sign) * 1000.0;
-    let median_single_sign_verify =
-        calculate_median(&mut time_to_verify_normal_sign) * 1000.0;
-    let fplusq_sign_verify = time_to_verify_fplus1_independent_signs * 1000.0;
-    let n_sign_verify = time_to_verify_all_independent_signs * 1000.0;
+impl CsvReport for EcdsaReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            ("hash_sha256", CsvCell::Timing(self.ecdsa.hash)),
+            (
+                "creation_single_sign",
+                CsvCell::Timing(Some(self.ecdsa.sign)),
+            ),
+            (
+                "verify_single_sign",
+                CsvCell::Timing(Some(self.ecdsa.verify)),
+            ),
+            (
+                "verify_fplus1_signs",
+                CsvCell::Timing(Some(self.ecdsa.verify_fplus1)),
+            ),
+            ("verify_n_signs", CsvCell::Timing(Some(self.ecdsa.verify_n))),
+            (
+                "verify_n_signs_parallel",
+                CsvCell::Parallel(self.ecdsa.verify_n_parallel.clone()),
+            ),
+            //secp256k1 only
+            ("recover_pubkey", CsvCell::Timing(self.ecdsa.recover_pubkey)),
+            (
+                "reject_wrong_msg",
+                CsvCell::Timing(Some(self.ecdsa.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey",
+                CsvCell::Timing(self.ecdsa.reject_wrong_pubkey),
+            ),
+            (
+                "reject_tampered_sig",
+                CsvCell::Timing(self.ecdsa.reject_tampered_sign),
+            ),
+            ("throughput", CsvCell::Throughput(self.ecdsa.throughput)),
+            (
+                "verify_failures",
+                CsvCell::value(self.ecdsa.verify_failures),
+            ),
+        ]);
+        cells
+    }
 
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        vec![&self.ecdsa]
+    }
+}
 
-    //OUTPUT
-    println!("for sign in g1 : ");
-    println!(
-        "mean time to create single bls sign: {:.4} ms",
-        mean_single_sign_creation
-    );
-    println!(
-        "median time to create single bls sign: {:.4} ms",
-        median_single_sign_creation
-    );
-    println!(
-        "mean time to verify single bls sign: {:.4} ms",
-        mean_single_sign_verify
-    );
-    println!(
-        "meadian time to verify single bls sign: {:.4} ms",
-        median_single_sign_verify
-    );
-    println!(
-        "time takes to verify f+1 signs: {:.4} ms",
-        fplusq_sign_verify
-    );
-    println!(
-        "time takes to verify n signs: {:.4} ms",
-        n_sign_verify
-    );
-    println!("");
+fn report(nodes: usize, params: &BenchParams, ecdsa: SchemeReport) -> EcdsaReport {
+    EcdsaReport {
+        run: RunInfo::new(nodes, params),
+        ecdsa,
+    }
+}
 
+pub fn benchmark_ecdsa_secp256k1(
+    nodes: usize,
+    params: &BenchParams,
+) -> Result<EcdsaReport, BenchError> {
+    let ecdsa = benchmark_scheme(&mut EcdsaSecp256k1, nodes, params)?;
+    Ok(report(nodes, params, ecdsa))
+}
 
-    (
-        mean_single_sign_creation,
-        mean_single_sign_verify,
-        n_sign_verify,
-    )
+pub fn benchmark_ecdsa_p256(nodes: usize, params: &BenchParams) -> Result<EcdsaReport, BenchError> {
+    let ecdsa = benchmark_scheme(&mut EcdsaP256, nodes, params)?;
+    Ok(report(nodes, params, ecdsa))
 }
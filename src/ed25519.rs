@@ -0,0 +1,152 @@
+use super::report::{CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::{benchmark_scheme, BenchError, BenchParams, SignatureScheme};
+use blsttc::rand::RngCore;
+use ed25519_dalek as dalek;
+use ed25519_dalek::Signer as _;
+use serde::Serialize;
+
+//eddsa over curve25519, the baseline the bls schemes are compared against
+pub struct Ed25519;
+
+impl SignatureScheme for Ed25519 {
+    type PublicKey = dalek::PublicKey;
+    type SecretKey = dalek::Keypair;
+    type Signature = dalek::Signature;
+    type AggregatePublicKey = ();
+    type AggregateSignature = ();
+    type MessageHash = ();
+
+    fn name(&self) -> &'static str {
+        "ed25519 sign"
+    }
+
+    fn keygen(&mut self, nodes: usize) -> Vec<(dalek::PublicKey, dalek::Keypair)> {
+        (0..nodes)
+            .map(|_| {
+                //same as `Keypair::generate`, which wants a rand 0.7 rng
+                let mut seed = [0u8; dalek::SECRET_KEY_LENGTH];
+                with_rng(|rng| rng.fill_bytes(&mut seed));
+                let secret = dalek::SecretKey::from_bytes(&seed).unwrap();
+                let public = dalek::PublicKey::from(&secret);
+                (public, dalek::Keypair { secret, public })
+            })
+            .collect()
+    }
+
+    fn sign(&self, sk: &dalek::Keypair, msg: &[u8]) -> dalek::Signature {
+        sk.sign(msg)
+    }
+
+    fn verify(&self, pk: &dalek::PublicKey, sig: &dalek::Signature, msg: &[u8]) -> bool {
+        pk.verify_strict(msg, sig).is_ok()
+    }
+
+    //one multiscalar multiplication over all signs, with random weights
+    //drawn by dalek itself; unlike `verify_strict` the batch equation is
+    //cofactored, which only matters for signs built from small-order points
+    fn verify_batch(
+        &self,
+        pks: &[dalek::PublicKey],
+        sigs: &[dalek::Signature],
+        msg: &[u8],
+    ) -> Option<bool> {
+        let msgs = vec![msg; sigs.len()];
+        Some(dalek::verify_batch(&msgs, sigs, pks).is_ok())
+    }
+
+    //flips a bit of the encoded point r
+    fn tampered_signature(&self, sig: &dalek::Signature) -> Option<dalek::Signature> {
+        let mut bytes = sig.to_bytes();
+        bytes[0] ^= 1;
+        dalek::Signature::from_bytes(&bytes).ok()
+    }
+
+    //r is the encoded identity point and s is zero
+    fn identity_signature(&self, _sig: &dalek::Signature) -> Option<dalek::Signature> {
+        let mut bytes = [0u8; dalek::SIGNATURE_LENGTH];
+        bytes[0] = 1;
+        dalek::Signature::from_bytes(&bytes).ok()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Ed25519Report {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub ed25519: SchemeReport,
+}
+
+impl CsvReport for Ed25519Report {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            (
+                "creation_single_sign",
+                CsvCell::Timing(Some(self.ed25519.sign)),
+            ),
+            (
+                "verify_single_sign",
+                CsvCell::Timing(Some(self.ed25519.verify)),
+            ),
+            (
+                "verify_fplus1_signs",
+                CsvCell::Timing(Some(self.ed25519.verify_fplus1)),
+            ),
+            (
+                "verify_n_signs",
+                CsvCell::Timing(Some(self.ed25519.verify_n)),
+            ),
+            (
+                "verify_fplus1_signs_batch",
+                CsvCell::Timing(self.ed25519.verify_fplus1_batch),
+            ),
+            (
+                "verify_n_signs_batch",
+                CsvCell::Timing(self.ed25519.verify_n_batch),
+            ),
+            (
+                "verify_n_signs_parallel",
+                CsvCell::Parallel(self.ed25519.verify_n_parallel.clone()),
+            ),
+            (
+                "reject_wrong_msg",
+                CsvCell::Timing(Some(self.ed25519.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey",
+                CsvCell::Timing(self.ed25519.reject_wrong_pubkey),
+            ),
+            (
+                "reject_tampered_sig",
+                CsvCell::Timing(self.ed25519.reject_tampered_sign),
+            ),
+            (
+                "reject_identity_sig",
+                CsvCell::Timing(self.ed25519.reject_identity_sign),
+            ),
+            ("throughput", CsvCell::Throughput(self.ed25519.throughput)),
+            (
+                "verify_failures",
+                CsvCell::value(self.ed25519.verify_failures),
+            ),
+        ]);
+        cells
+    }
+
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        vec![&self.ed25519]
+    }
+}
+
+pub fn benchmark_ed25519_signs(
+    nodes: usize,
+    params: &BenchParams,
+) -> Result<Ed25519Report, BenchError> {
+    let ed25519 = benchmark_scheme(&mut Ed25519, nodes, params)?;
+
+    Ok(Ed25519Report {
+        run: RunInfo::new(nodes, params),
+        ed25519,
+    })
+}
@@ -0,0 +1,13 @@
+use blsttc::group::Group;
+use blsttc::{G1Affine, G1Projective, G2Affine, G2Projective};
+
+//invalid signatures used to time how fast verification rejects them
+
+//a valid point of the curve that is not the signature
+pub fn tampered_g1(point: &G1Affine) -> G1Affine {
+    G1Affine::from(*point + G1Projective::generator())
+}
+
+pub fn tampered_g2(point: &G2Affine) -> G2Affine {
+    G2Affine::from(*point + G2Projective::generator())
+}
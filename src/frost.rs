@@ -0,0 +1,457 @@
+use super::report::{CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::{benchmark_scheme, BenchError, BenchParams, SignatureScheme};
+use blsttc::rand::RngCore;
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+use ed25519_dalek as dalek;
+use serde::Serialize;
+use sha2::{Digest, Sha512};
+use std::sync::{Arc, RwLock};
+
+//FROST(Ed25519, SHA-512) of rfc 9591 with a trusted dealer: a first round
+//where every node commits to two nonces, then a second round of sign
+//shares that sum to a plain ed25519 sign under the group key
+//
+//the dealer deals a share to every node of the committee, but only a
+//signing set of `threshold + 1` nodes commits to nonces and signs, the
+//fewest whose shares combine; unlike bls, all the nodes that committed
+//must answer
+//
+//a run is one signing session, the nonces are drawn once per run and every
+//node of the signing set signs the same message with them
+pub struct FrostEd25519 {
+    pub threshold: usize,
+    //set by keygen
+    group_key: Option<dalek::PublicKey>,
+    //lagrange coefficients of the signing set, fixed by the dealer
+    lambdas: Vec<Scalar>,
+    //set by the nonce round
+    nonces: Option<Nonces>,
+    //sessions the coordinator derived from the commitment list, by message;
+    //shares are checked and combined under these, never under what a
+    //signer claims
+    sessions: RwLock<Vec<(Vec<u8>, Arc<Session>)>>,
+}
+
+//secret share of a node, with the index its nonces are kept under
+pub struct SigningShare {
+    index: usize,
+    share: Scalar,
+}
+
+//share of a node; it carries the hash of the message it signed, so the
+//coordinator can combine the shares under the session it derived for it
+#[derive(Clone, Copy, Debug)]
+pub struct SignatureShare {
+    signer: usize,
+    z: Scalar,
+    msg_hash: [u8; 64],
+}
+
+//nonces of the first round, kept by index of the node
+struct Nonces {
+    secret: Vec<[Scalar; 2]>,
+    //hiding and binding commitments
+    commitments: Vec<[EdwardsPoint; 2]>,
+    //sum of the hiding commitments, which don't depend on the message
+    hiding_sum: EdwardsPoint,
+    //hash of the encoded commitment list, which every binding factor
+    //commits to
+    list_hash: [u8; 64],
+}
+
+//values every node derives from the commitments and the message
+#[derive(Debug)]
+pub struct Session {
+    msg_hash: [u8; 64],
+    binding_factors: Vec<Scalar>,
+    //group commitment, the r of the final sign
+    commitment: EdwardsPoint,
+    challenge: Scalar,
+}
+
+const CONTEXT: &[u8] = b"FROST-ED25519-SHA512-v1";
+
+//hash of the ciphersuite for the given purpose, `tag` is empty for the
+//challenge which is the plain ed25519 one
+fn hash(tag: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    if !tag.is_empty() {
+        hasher.update(CONTEXT);
+        hasher.update(tag);
+    }
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn hash_to_scalar(tag: &[u8], parts: &[&[u8]]) -> Scalar {
+    Scalar::from_bytes_mod_order_wide(&hash(tag, parts))
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    with_rng(|rng| rng.fill_bytes(&mut bytes));
+    bytes
+}
+
+//nodes are identified by their index plus one, zero is the group secret
+fn identifier(index: usize) -> Scalar {
+    Scalar::from(index as u64 + 1)
+}
+
+//lagrange coefficients at zero of every identifier in `0..nodes`
+fn lagrange_coefficients(nodes: usize) -> Vec<Scalar> {
+    let mut denominators: Vec<_> = (0..nodes)
+        .map(|i| {
+            (0..nodes)
+                .filter(|&j| j != i)
+                .map(|j| identifier(j) - identifier(i))
+                .product()
+        })
+        .collect();
+    Scalar::batch_invert(&mut denominators);
+    let numerator: Scalar = (0..nodes).map(identifier).product();
+
+    denominators
+        .into_iter()
+        .enumerate()
+        .map(|(i, inverse)| numerator * identifier(i).invert() * inverse)
+        .collect()
+}
+
+impl FrostEd25519 {
+    pub fn new(threshold: usize) -> Self {
+        FrostEd25519 {
+            threshold,
+            group_key: None,
+            lambdas: Vec::new(),
+            nonces: None,
+            sessions: RwLock::new(Vec::new()),
+        }
+    }
+
+    fn group_key(&self) -> &dalek::PublicKey {
+        self.group_key.as_ref().expect("keygen must run first")
+    }
+
+    fn nonces(&self) -> &Nonces {
+        self.nonces
+            .as_ref()
+            .expect("nonces are committed before signing")
+    }
+
+    fn session(&self, msg: &[u8]) -> Session {
+        let group_key = self.group_key().as_bytes();
+        let nonces = self.nonces();
+        let msg_hash = hash(b"msg", &[msg]);
+
+        let binding_factors: Vec<_> = (0..nonces.commitments.len())
+            .map(|i| {
+                hash_to_scalar(
+                    b"rho",
+                    &[
+                        group_key,
+                        &msg_hash,
+                        &nonces.list_hash,
+                        identifier(i).as_bytes(),
+                    ],
+                )
+            })
+            .collect();
+        let commitment = nonces.hiding_sum
+            + EdwardsPoint::vartime_multiscalar_mul(
+                &binding_factors,
+                nonces.commitments.iter().map(|[_, binding]| binding),
+            );
+        let challenge = hash_to_scalar(b"", &[commitment.compress().as_bytes(), group_key, msg]);
+
+        Session {
+            msg_hash,
+            binding_factors,
+            commitment,
+            challenge,
+        }
+    }
+
+    //session of `msg` as the coordinator derives it, once per message
+    fn derived_session(&self, msg: &[u8]) -> Arc<Session> {
+        let cached = self
+            .sessions
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|(signed, session)| (signed.as_slice() == msg).then(|| session.clone()));
+        cached.unwrap_or_else(|| {
+            let session = Arc::new(self.session(msg));
+            self.sessions
+                .write()
+                .unwrap()
+                .push((msg.to_vec(), session.clone()));
+            session
+        })
+    }
+
+    fn sign_share(&self, sk: &SigningShare, session: &Session) -> SignatureShare {
+        let [hiding, binding] = self.nonces().secret[sk.index];
+        let z = hiding
+            + binding * session.binding_factors[sk.index]
+            + self.lambdas[sk.index] * sk.share * session.challenge;
+
+        SignatureShare {
+            signer: sk.index,
+            z,
+            msg_hash: session.msg_hash,
+        }
+    }
+
+    fn verify_share(&self, pk: &EdwardsPoint, sig: &SignatureShare, session: &Session) -> bool {
+        let nonces = self.nonces();
+        let (Some([hiding, binding]), Some(lambda)) = (
+            nonces.commitments.get(sig.signer),
+            self.lambdas.get(sig.signer),
+        ) else {
+            return false;
+        };
+        let binding_factor = session.binding_factors[sig.signer];
+
+        EdwardsPoint::vartime_multiscalar_mul(
+            [sig.z, -binding_factor, -(session.challenge * lambda)],
+            [ED25519_BASEPOINT_TABLE.basepoint(), *binding, *pk],
+        ) == *hiding
+    }
+}
+
+impl SignatureScheme for FrostEd25519 {
+    type PublicKey = EdwardsPoint;
+    type SecretKey = SigningShare;
+    type Signature = SignatureShare;
+    type AggregatePublicKey = ();
+    type AggregateSignature = dalek::Signature;
+    //binding factors, group commitment and challenge of the message
+    type MessageHash = Arc<Session>;
+
+    fn name(&self) -> &'static str {
+        "frost ed25519 sign share"
+    }
+
+    //the dealer splits a random secret with a polynomial of degree
+    //`threshold`, any `threshold + 1` shares recover it; the keys of the
+    //signing set are returned, the shares of the other nodes are dealt but
+    //take no part in the session
+    fn keygen(&mut self, nodes: usize) -> Vec<(EdwardsPoint, SigningShare)> {
+        let coefficients: Vec<_> = (0..=self.threshold)
+            .map(|_| Scalar::from_bytes_mod_order_wide(&random_bytes()))
+            .collect();
+        let mut keypairs: Vec<_> = (0..nodes)
+            .map(|index| {
+                let share = coefficients
+                    .iter()
+                    .rev()
+                    .fold(Scalar::zero(), |acc, coefficient| {
+                        acc * identifier(index) + coefficient
+                    });
+                (
+                    &share * &ED25519_BASEPOINT_TABLE,
+                    SigningShare { index, share },
+                )
+            })
+            .collect();
+        let group_key = &coefficients[0] * &ED25519_BASEPOINT_TABLE;
+        let signers = (self.threshold + 1).min(nodes);
+        keypairs.truncate(signers);
+
+        self.group_key =
+            Some(dalek::PublicKey::from_bytes(group_key.compress().as_bytes()).unwrap());
+        self.lambdas = lagrange_coefficients(signers);
+        self.nonces = None;
+        self.sessions.get_mut().unwrap().clear();
+
+        keypairs
+    }
+
+    //every node of the signing set draws its nonces from fresh randomness,
+    //rfc 9591 also hashes in the share which only guards against a broken
+    //rng; the coordinator then hashes the commitment list it sends out
+    fn commit_nonces(&mut self) -> bool {
+        let secret: Vec<[Scalar; 2]> = (0..self.lambdas.len())
+            .map(|_| [0; 2].map(|_| hash_to_scalar(b"nonce", &[&random_bytes::<32>()])))
+            .collect();
+        let commitments: Vec<[EdwardsPoint; 2]> = secret
+            .iter()
+            .map(|nonces| nonces.map(|k| &k * &ED25519_BASEPOINT_TABLE))
+            .collect();
+        let hiding_sum = commitments.iter().map(|[hiding, _]| hiding).sum();
+        let mut list = Sha512::new();
+        list.update(CONTEXT);
+        list.update(b"com");
+        for (i, [hiding, binding]) in commitments.iter().enumerate() {
+            list.update(identifier(i).as_bytes());
+            list.update(hiding.compress().as_bytes());
+            list.update(binding.compress().as_bytes());
+        }
+
+        self.nonces = Some(Nonces {
+            secret,
+            commitments,
+            hiding_sum,
+            list_hash: list.finalize().into(),
+        });
+        self.sessions.get_mut().unwrap().clear();
+        true
+    }
+
+    //every signer derives the session of `msg` on its own
+    fn sign(&self, sk: &SigningShare, msg: &[u8]) -> SignatureShare {
+        self.sign_share(sk, &self.session(msg))
+    }
+
+    fn verify(&self, pk: &EdwardsPoint, sig: &SignatureShare, msg: &[u8]) -> bool {
+        self.verify_share(pk, sig, &self.derived_session(msg))
+    }
+
+    //derived by the coordinator, so the timing is that of the first
+    //derivation of the message
+    fn hash_message(&self, msg: &[u8]) -> Option<Arc<Session>> {
+        Some(self.derived_session(msg))
+    }
+
+    fn sign_hashed(&self, sk: &SigningShare, session: &Arc<Session>) -> Option<SignatureShare> {
+        Some(self.sign_share(sk, session))
+    }
+
+    fn verify_hashed(
+        &self,
+        pk: &EdwardsPoint,
+        sig: &SignatureShare,
+        session: &Arc<Session>,
+    ) -> Option<bool> {
+        Some(self.verify_share(pk, sig, session))
+    }
+
+    //z + 1
+    fn tampered_signature(&self, sig: &SignatureShare) -> Option<SignatureShare> {
+        Some(SignatureShare {
+            z: sig.z + Scalar::one(),
+            ..*sig
+        })
+    }
+
+    //the coordinator only combines shares of a message it derived the
+    //session of, r is the group commitment of that session
+    fn aggregate_signatures(&self, sigs: &[(usize, SignatureShare)]) -> Option<dalek::Signature> {
+        let (_, first) = sigs.first()?;
+        let session = self
+            .sessions
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(_, session)| session.msg_hash == first.msg_hash)
+            .map(|(_, session)| session.clone())?;
+        let z: Scalar = sigs.iter().map(|(_, sig)| sig.z).sum();
+        let mut bytes = [0u8; dalek::SIGNATURE_LENGTH];
+        bytes[..32].copy_from_slice(session.commitment.compress().as_bytes());
+        bytes[32..].copy_from_slice(z.as_bytes());
+        dalek::Signature::from_bytes(&bytes).ok()
+    }
+
+    //the combined sign is checked as any ed25519 sign
+    fn verify_aggregate(
+        &self,
+        _agg_pk: Option<&()>,
+        agg_sig: &dalek::Signature,
+        msg: &[u8],
+    ) -> bool {
+        self.group_key().verify_strict(msg, agg_sig).is_ok()
+    }
+}
+
+//same columns as the threshold bls report, for a single scheme
+#[derive(Clone, Debug, Serialize)]
+pub struct FrostReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub threshold: usize,
+    pub frost: SchemeReport,
+}
+
+impl CsvReport for FrostReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            ("threshold", CsvCell::value(self.threshold)),
+            //shares of all nodes and the coefficients of the signing set
+            ("dealer_keygen", CsvCell::Timing(Some(self.frost.keygen))),
+            (
+                "commit_nonces_round",
+                CsvCell::Timing(self.frost.commit_nonces),
+            ),
+            ("derive_session", CsvCell::Timing(self.frost.hash)),
+            (
+                "creation_single_sign_share",
+                CsvCell::Timing(Some(self.frost.sign)),
+            ),
+            (
+                "verify_single_sign_share",
+                CsvCell::Timing(Some(self.frost.verify)),
+            ),
+            //the signs of the `threshold + 1` nodes of the signing set
+            (
+                "verify_tplus1_signshares",
+                CsvCell::Timing(Some(self.frost.verify_n)),
+            ),
+            (
+                "verify_tplus1_signshares_parallel",
+                CsvCell::Parallel(self.frost.verify_n_parallel.clone()),
+            ),
+            (
+                "creation_threshold_sig",
+                CsvCell::Timing(self.frost.aggregate_sign),
+            ),
+            (
+                "verify_threshold_sig",
+                CsvCell::Timing(self.frost.verify_aggregate),
+            ),
+            (
+                "reject_wrong_msg",
+                CsvCell::Timing(Some(self.frost.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey",
+                CsvCell::Timing(self.frost.reject_wrong_pubkey),
+            ),
+            (
+                "reject_tampered_sig",
+                CsvCell::Timing(self.frost.reject_tampered_sign),
+            ),
+            ("throughput", CsvCell::Throughput(self.frost.throughput)),
+            (
+                "verify_failures",
+                CsvCell::value(self.frost.verify_failures),
+            ),
+        ]);
+        cells
+    }
+
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        vec![&self.frost]
+    }
+}
+
+pub fn benchmark_frost(
+    nodes: usize,
+    threshold: usize,
+    params: &BenchParams,
+) -> Result<FrostReport, BenchError> {
+    let frost = benchmark_scheme(&mut FrostEd25519::new(threshold), nodes, params)?;
+
+    Ok(FrostReport {
+        run: RunInfo::new(nodes, params),
+        threshold,
+        frost,
+    })
+}
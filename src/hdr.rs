@@ -0,0 +1,79 @@
+use hdrhistogram::serialization::interval_log::{IntervalLogWriterBuilder, Tag};
+use hdrhistogram::serialization::V2DeflateSerializer;
+use hdrhistogram::Histogram;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime};
+
+//latencies in nanoseconds to 3 significant digits; the range grows with
+//the largest value recorded
+pub fn new_histogram() -> Histogram<u64> {
+    Histogram::new(3).expect("3 significant digits are supported")
+}
+
+//histogram of an operation with the tag it is written under; tags can't
+//hold spaces or commas, so both are replaced
+pub fn tagged(tag: &str, histogram: &Histogram<u64>) -> (String, Histogram<u64>) {
+    (tag.replace([' ', ','], "_"), histogram.clone())
+}
+
+//histograms of a scheme in the hdrhistogram interval log format, one
+//interval per run of the sweep
+pub struct IntervalLog {
+    file: BufWriter<File>,
+    serializer: V2DeflateSerializer,
+    //timestamps of the intervals are relative to the base time of the log
+    start: Instant,
+}
+
+impl IntervalLog {
+    pub fn create(path: &Path, stem: &str) -> Self {
+        let mut file = BufWriter::new(File::create(path).unwrap());
+        let mut serializer = V2DeflateSerializer::new();
+        let now = SystemTime::now();
+        IntervalLogWriterBuilder::new()
+            .add_comment("[Histogram log format version 1.3]")
+            .add_comment(&format!(
+                "{} latencies in nanoseconds, tagged operation/nodes/message size",
+                stem
+            ))
+            .with_start_time(now)
+            .with_base_time(now)
+            .begin_log_with(&mut file, &mut serializer)
+            .unwrap();
+        writeln!(
+            file,
+            "\"StartTimestamp\",\"Interval_Length\",\"Interval_Max\",\"Interval_Compressed_Histogram\""
+        )
+        .unwrap();
+
+        IntervalLog {
+            file,
+            serializer,
+            start: Instant::now(),
+        }
+    }
+
+    //writes the histograms of a run that started at `run_start` and ends now
+    pub fn write(
+        &mut self,
+        run_start: Instant,
+        nodes: usize,
+        message_size: usize,
+        histograms: &[&(String, Histogram<u64>)],
+    ) {
+        let start_timestamp = run_start.duration_since(self.start);
+        let duration = run_start.elapsed();
+        //the header was written by `create`, an empty builder adds none
+        let mut log = IntervalLogWriterBuilder::new()
+            .begin_log_with(&mut self.file, &mut self.serializer)
+            .unwrap();
+        for (tag, histogram) in histograms {
+            let tag = format!("{}/n{}/m{}", tag, nodes, message_size);
+            log.write_histogram(histogram, start_timestamp, duration, Tag::new(&tag))
+                .unwrap();
+        }
+        self.file.flush().unwrap();
+    }
+}
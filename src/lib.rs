@@ -1,6 +1,909 @@
+pub mod cli;
+pub mod config;
+pub mod ecdsa;
+pub mod ed25519;
+pub mod forgery;
+pub mod frost;
+pub mod hdr;
 pub mod multisig_bls;
 pub mod multisig_bls_nizk;
+pub mod multisig_bls_pop;
+pub mod musig2;
+pub mod output;
+pub mod report;
+pub mod rng;
+pub mod schnorr;
 pub mod simple_bls;
 pub mod stats;
 pub mod threshold_bls;
-pub mod ecdsa;
+
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use report::{Measurement, ParallelVerify, Rate, SchemeReport, Throughput};
+use serde::Deserialize;
+use stats::{Samples, StatsError};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::hint::black_box;
+use std::slice;
+use std::thread;
+use std::time::{Duration, Instant};
+
+//curve group the signatures of a bls scheme live in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Group {
+    G1,
+    G2,
+}
+
+//size in bytes of the payload signed when no sizes are configured
+pub const DEFAULT_MESSAGE_SIZE: usize = 24;
+
+//settings shared by every scheme for one point of the sweep
+#[derive(Clone, Debug)]
+pub struct BenchParams {
+    pub message: Vec<u8>,
+    //untimed runs of every operation before it is measured
+    pub warmup: usize,
+    //timed runs of every operation, stats are computed over all of them
+    pub iterations: usize,
+    //keys and nonces of every scheme are derived from this seed
+    pub seed: u64,
+    //sign and verify a precomputed hash, leaving hash-to-curve out of the
+    //timings
+    pub prehashed: bool,
+    //summarise the timings on the fly instead of keeping every sample
+    pub streaming: bool,
+    //how long each operation runs in a closed loop for its throughput, if
+    //throughput is measured
+    pub throughput: Option<Duration>,
+    //threads of the multi-threaded throughput, and the most threads the
+    //parallel verification is run on
+    pub threads: usize,
+    //also verify the n signs on pools of 1 to `threads` threads
+    pub parallel_verify: bool,
+}
+
+impl BenchParams {
+    //empty timings of one operation
+    pub fn samples(&self) -> Samples {
+        Samples::new(!self.streaming)
+    }
+}
+
+impl Default for BenchParams {
+    fn default() -> Self {
+        let seed = rng::random_seed();
+        BenchParams {
+            message: rng::random_message(seed, DEFAULT_MESSAGE_SIZE),
+            warmup: 1,
+            iterations: 1,
+            seed,
+            prehashed: false,
+            streaming: false,
+            throughput: None,
+            threads: 1,
+            parallel_verify: false,
+        }
+    }
+}
+
+//a signature scheme that can be driven by `benchmark_scheme`; the
+//throughput is measured from several threads at once, so the scheme and
+//its keys and signatures are shared between threads
+pub trait SignatureScheme: Sync {
+    //compared with the keys recovered from signs, and with the key of the
+    //next node for the wrong pubkey rejection
+    type PublicKey: PartialEq + Sync;
+    type SecretKey: Sync;
+    type Signature: Clone + Sync;
+    //sent back from the pool the parallel aggregates were reduced on, and
+    //compared with the sequential ones
+    type AggregatePublicKey: PartialEq + Send + Sync;
+    type AggregateSignature: PartialEq + Send + Sync;
+    //point the message is hashed to, `()` for schemes that sign it as is
+    type MessageHash: Sync;
+
+    //label used when printing results
+    fn name(&self) -> &'static str;
+
+    fn keygen(&mut self, nodes: usize) -> Vec<(Self::PublicKey, Self::SecretKey)>;
+
+    //first round of two-round schemes: every node commits to fresh nonces
+    //and the commitments are aggregated, signing then uses the aggregate;
+    //`false` for schemes that sign in a single round
+    fn commit_nonces(&mut self) -> bool {
+        false
+    }
+
+    //attaches to `pk` a proof that its holder knows `sk`, so aggregates
+    //can't take rogue keys built from the keys of others; `false` for
+    //schemes whose keys carry no proof
+    fn prove_possession(&self, _pk: &mut Self::PublicKey, _sk: &Self::SecretKey) -> bool {
+        false
+    }
+
+    //checks the proof attached to `pk` and records the result on it,
+    //aggregation then refuses keys that weren't proven; `false` for
+    //schemes whose keys carry no proof
+    fn verify_possession(&self, _pk: &mut Self::PublicKey) -> bool {
+        false
+    }
+
+    //key that cancels out `pks` in their aggregate, published with a proof
+    //made with the only key the attacker knows
+    fn rogue_public_key(&self, _pks: &[Self::PublicKey]) -> Option<Self::PublicKey> {
+        None
+    }
+
+    fn sign(&self, sk: &Self::SecretKey, msg: &[u8]) -> Self::Signature;
+
+    fn verify(&self, pk: &Self::PublicKey, sig: &Self::Signature, msg: &[u8]) -> bool;
+
+    //verifies all of `sigs` on `msg` at once, each against the pubkey at the
+    //same index; `None` for schemes without batch verification
+    fn verify_batch(
+        &self,
+        _pks: &[Self::PublicKey],
+        _sigs: &[Self::Signature],
+        _msg: &[u8],
+    ) -> Option<bool> {
+        None
+    }
+
+    //schemes that hash the message to the curve override this and the
+    //`*_hashed` methods, so hashing can be timed on its own
+    fn hash_message(&self, _msg: &[u8]) -> Option<Self::MessageHash> {
+        None
+    }
+
+    //only called with a hash returned by `hash_message`; `None` for schemes
+    //that sign the message as is
+    fn sign_hashed(
+        &self,
+        _sk: &Self::SecretKey,
+        _hash: &Self::MessageHash,
+    ) -> Option<Self::Signature> {
+        None
+    }
+
+    fn verify_hashed(
+        &self,
+        _pk: &Self::PublicKey,
+        _sig: &Self::Signature,
+        _hash: &Self::MessageHash,
+    ) -> Option<bool> {
+        None
+    }
+
+    //pubkey of the signer of `sig` on `msg`; `None` for schemes whose signs
+    //don't carry a recovery id
+    fn recover_public_key(&self, _sig: &Self::Signature, _msg: &[u8]) -> Option<Self::PublicKey> {
+        None
+    }
+
+    //aggregation is optional: schemes that can combine signatures (multisig
+    //aggregation, threshold combining) override the `aggregate_*` methods,
+    //everything else keeps the defaults and gets no aggregate timings
+    fn aggregate_public_keys(&self, _pks: &[Self::PublicKey]) -> Option<Self::AggregatePublicKey> {
+        None
+    }
+
+    //signatures are paired with the index of the node that produced them
+    fn aggregate_signatures(
+        &self,
+        _sigs: &[(usize, Self::Signature)],
+    ) -> Option<Self::AggregateSignature> {
+        None
+    }
+
+    //same as the sequential methods above, reduced across the threads of
+    //the current rayon pool; only for schemes whose aggregate is a plain sum
+    fn aggregate_public_keys_parallel(
+        &self,
+        _pks: &[Self::PublicKey],
+    ) -> Option<Self::AggregatePublicKey> {
+        None
+    }
+
+    fn aggregate_signatures_parallel(
+        &self,
+        _sigs: &[(usize, Self::Signature)],
+    ) -> Option<Self::AggregateSignature> {
+        None
+    }
+
+    //`agg_pk` is `None` for schemes that verify against a key fixed at keygen
+    fn verify_aggregate(
+        &self,
+        _agg_pk: Option<&Self::AggregatePublicKey>,
+        _agg_sig: &Self::AggregateSignature,
+        _msg: &[u8],
+    ) -> bool {
+        false
+    }
+
+    //invalid signatures for the rejection timings, built from a valid
+    //`sig`; schemes that can't build one keep the default and get no timing
+
+    //`sig` with its point moved to another point of the curve
+    fn tampered_signature(&self, _sig: &Self::Signature) -> Option<Self::Signature> {
+        None
+    }
+
+    //`sig` with its point replaced by the identity
+    fn identity_signature(&self, _sig: &Self::Signature) -> Option<Self::Signature> {
+        None
+    }
+
+    //`sig` with a proof that doesn't hold, for schemes that attach one
+    fn forged_proof_signature(
+        &self,
+        _sk: &Self::SecretKey,
+        _sig: &Self::Signature,
+        _msg: &[u8],
+    ) -> Option<Self::Signature> {
+        None
+    }
+}
+
+//number of signatures needed to outvote f faulty nodes out of `nodes`,
+//where f = (nodes - 1) / 3; a single node outvotes nobody and needs its own
+pub fn fplus1(nodes: usize) -> usize {
+    (nodes.saturating_sub(1) / 3 + 1).min(nodes)
+}
+
+//why a scheme produced no report
+#[derive(Debug)]
+pub enum BenchError {
+    //the run can't be measured with these settings, e.g. no nodes
+    Params {
+        scheme: &'static str,
+        reason: &'static str,
+    },
+    //an operation whose timings can't be summarised
+    Summary {
+        scheme: &'static str,
+        operation: &'static str,
+        source: StatsError,
+    },
+}
+
+impl fmt::Display for BenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BenchError::Params { scheme, reason } => {
+                write!(f, "cannot benchmark {}: {}", scheme, reason)
+            }
+            BenchError::Summary {
+                scheme,
+                operation,
+                source,
+            } => write!(
+                f,
+                "cannot summarise {} of {}: {}",
+                operation, scheme, source
+            ),
+        }
+    }
+}
+
+impl Error for BenchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BenchError::Params { .. } => None,
+            BenchError::Summary { source, .. } => Some(source),
+        }
+    }
+}
+
+//runs `f` once and returns its result with the elapsed time in seconds
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, f64) {
+    let start_time = Instant::now();
+    let result = f();
+    let end_time = Instant::now();
+
+    (result, end_time.duration_since(start_time).as_secs_f64())
+}
+
+//runs `f` `params.warmup` times untimed and then `params.iterations` times
+//timed, adding one sample in milliseconds per timed run to `samples`;
+//returns the result of the last run
+pub fn repeat<T>(params: &BenchParams, samples: &mut Samples, mut f: impl FnMut() -> T) -> T {
+    for _ in 0..params.warmup {
+        f();
+    }
+
+    let mut result = None;
+    for _ in 0..params.iterations {
+        let (r, elapsed_time) = timed(&mut f);
+        result = Some(r);
+        samples.push(elapsed_time * 1000.0);
+    }
+
+    result.expect("iterations must be greater than 0")
+}
+
+//runs `op` back to back on `threads` threads until `duration` has passed,
+//giving each call its index on its thread; returns the calls completed per
+//second over all threads
+fn ops_per_sec<T>(threads: usize, duration: Duration, op: impl Fn(usize) -> T + Sync) -> f64 {
+    let start = Instant::now();
+    let ops: usize = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut ops = 0;
+                    while start.elapsed() < duration {
+                        black_box(op(ops));
+                        ops += 1;
+                    }
+                    ops
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    });
+
+    ops as f64 / start.elapsed().as_secs_f64()
+}
+
+//sustained rate of `op` on a single thread and on `params.threads` threads
+fn rate<T>(params: &BenchParams, duration: Duration, op: impl Fn(usize) -> T + Sync) -> Rate {
+    Rate {
+        single_thread: ops_per_sec(1, duration, &op),
+        multi_thread: ops_per_sec(params.threads, duration, &op),
+    }
+}
+
+//times rejecting one invalid input per node, as built by `invalid`, and
+//returns the samples; `None` if the scheme can't build it. an input that is
+//accepted is counted in `failures`
+fn time_to_reject<'a, S: SignatureScheme>(
+    scheme: &S,
+    nodes: usize,
+    params: &BenchParams,
+    failures: &mut usize,
+    invalid: impl Fn(usize) -> Option<(&'a S::PublicKey, S::Signature, &'a [u8])>,
+) -> Option<Samples>
+where
+    S::PublicKey: 'a,
+{
+    let mut samples = params.samples();
+    for node in 0..nodes {
+        let (pk, sig, msg) = invalid(node)?;
+        let valid = repeat(params, &mut samples, || scheme.verify(pk, &sig, msg));
+        *failures += usize::from(valid);
+    }
+
+    Some(samples)
+}
+
+//times keygen, sign, verify and (if supported) aggregation of `scheme`
+//for a committee of `nodes` signers
+pub fn benchmark_scheme<S: SignatureScheme>(
+    scheme: &mut S,
+    nodes: usize,
+    params: &BenchParams,
+) -> Result<SchemeReport, BenchError> {
+    //every operation is timed at least once, over the keys of every node
+    let invalid = |reason| BenchError::Params {
+        scheme: scheme.name(),
+        reason,
+    };
+    if nodes == 0 {
+        return Err(invalid("no nodes"));
+    }
+    if params.iterations == 0 {
+        return Err(invalid("iterations must be greater than 0"));
+    }
+
+    let msg = &params.message[..];
+    //same seed and node count give the same keys whatever else is run
+    rng::reseed(params.seed, nodes as u64);
+    //the timed runs draw from a copy of the rng and what is kept is drawn
+    //once more untimed, so the keys and nonces don't depend on the warmup
+    //and iteration counts
+    let mut time_to_keygen = params.samples();
+    rng::rewind_after(|| repeat(params, &mut time_to_keygen, || scheme.keygen(nodes)));
+    let mut keypairs = scheme.keygen(nodes);
+    //schemes that sign with a subset of the committee only return the keys
+    //of that subset, everything after keygen runs over them
+    let nodes = keypairs.len();
+
+    //for the nonce round of two-round schemes, for all nodes at once
+    let mut time_to_commit_nonces = params.samples();
+    rng::rewind_after(|| {
+        repeat(params, &mut time_to_commit_nonces, || {
+            scheme.commit_nonces()
+        })
+    });
+    let two_round = scheme.commit_nonces();
+
+    //for hashing the message to the curve
+    let mut time_to_hash = params.samples();
+    let hash = repeat(params, &mut time_to_hash, || scheme.hash_message(msg));
+
+    //in pre-hashed mode sign and verify take the hash computed above; the
+    //rejection and aggregate timings always hash the message
+    let prehash = hash.as_ref().filter(|_| params.prehashed);
+    //schemes that hash the message but have no hashed variant fall back to it
+    let sign_msg = |sk: &S::SecretKey| {
+        prehash
+            .and_then(|hash| scheme.sign_hashed(sk, hash))
+            .unwrap_or_else(|| scheme.sign(sk, msg))
+    };
+    let verify_msg = |pk: &S::PublicKey, sig: &S::Signature| {
+        prehash
+            .and_then(|hash| scheme.verify_hashed(pk, sig, hash))
+            .unwrap_or_else(|| scheme.verify(pk, sig, msg))
+    };
+
+    //for creating single sign
+    let mut signs = Vec::new();
+    let mut time_to_sign = params.samples();
+    for (_, sk) in &keypairs {
+        rng::rewind_after(|| repeat(params, &mut time_to_sign, || sign_msg(sk)));
+        signs.push(sign_msg(sk));
+    }
+
+    //each verification that returns the wrong result (a valid signature
+    //rejected or an invalid one accepted) is counted and marks the run invalid
+    let mut verify_failures = 0;
+
+    //for the proofs of possession of all nodes, and checking them before
+    //the keys are aggregated; both are part of the setup
+    let mut time_to_prove_possession = params.samples();
+    let mut prove_possession = || {
+        keypairs
+            .iter_mut()
+            .all(|(pk, sk)| scheme.prove_possession(pk, sk))
+    };
+    rng::rewind_after(|| repeat(params, &mut time_to_prove_possession, &mut prove_possession));
+    let proves_possession = prove_possession();
+    let time_to_verify_possession = proves_possession.then(|| {
+        let mut samples = params.samples();
+        verify_failures += repeat(params, &mut samples, || {
+            keypairs
+                .iter_mut()
+                .map(|(pk, _)| usize::from(!scheme.verify_possession(pk)))
+                .sum::<usize>()
+        });
+        samples
+    });
+
+    //for verifying single sign
+    let mut time_to_verify = params.samples();
+    let mut individually_valid = Vec::new();
+    for ((pk, _), sign) in keypairs.iter().zip(&signs) {
+        let valid = repeat(params, &mut time_to_verify, || verify_msg(pk, sign));
+        verify_failures += usize::from(!valid);
+        individually_valid.push(valid);
+    }
+
+    //verifies the first `count` signs, returns how many of them failed
+    let verify_signs = |count: usize| {
+        keypairs
+            .iter()
+            .zip(&signs)
+            .take(count)
+            .filter(|((pk, _), sign)| !verify_msg(pk, sign))
+            .count()
+    };
+
+    //for rejecting invalid signs
+    let mut wrong_msg = msg.to_vec();
+    match wrong_msg.first_mut() {
+        Some(byte) => *byte ^= 1,
+        None => wrong_msg.push(0),
+    }
+    let time_to_reject_wrong_msg =
+        time_to_reject(scheme, nodes, params, &mut verify_failures, |node| {
+            Some((&keypairs[node].0, signs[node].clone(), &wrong_msg[..]))
+        });
+    //the key of the next node whose key differs, which needs a committee of
+    //two or more; threshold schemes with t = 0 deal the same key to every node
+    let time_to_reject_wrong_pubkey =
+        time_to_reject(scheme, nodes, params, &mut verify_failures, |node| {
+            let (pk, _) = keypairs
+                .iter()
+                .cycle()
+                .skip(node + 1)
+                .take(nodes - 1)
+                .find(|(pk, _)| *pk != keypairs[node].0)?;
+            Some((pk, signs[node].clone(), msg))
+        });
+    let time_to_reject_tampered_sign =
+        time_to_reject(scheme, nodes, params, &mut verify_failures, |node| {
+            Some((
+                &keypairs[node].0,
+                scheme.tampered_signature(&signs[node])?,
+                msg,
+            ))
+        });
+    let time_to_reject_identity_sign =
+        time_to_reject(scheme, nodes, params, &mut verify_failures, |node| {
+            Some((
+                &keypairs[node].0,
+                scheme.identity_signature(&signs[node])?,
+                msg,
+            ))
+        });
+    let time_to_reject_forged_proof =
+        time_to_reject(scheme, nodes, params, &mut verify_failures, |node| {
+            let (pk, sk) = &keypairs[node];
+            Some((
+                pk,
+                scheme.forged_proof_signature(sk, &signs[node], msg)?,
+                msg,
+            ))
+        });
+
+    //for f+1 signs
+    let mut time_to_verify_fplus1 = params.samples();
+    verify_failures += repeat(params, &mut time_to_verify_fplus1, || {
+        verify_signs(fplus1(nodes))
+    });
+
+    //for all signs
+    let mut time_to_verify_n = params.samples();
+    verify_failures += repeat(params, &mut time_to_verify_n, || verify_signs(nodes));
+
+    //for all signs verified on a worker pool, for every pool size
+    let max_threads = if params.parallel_verify {
+        params.threads
+    } else {
+        0
+    };
+    let mut time_to_verify_n_parallel = Vec::new();
+    for threads in 1..=max_threads {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let mut samples = params.samples();
+        verify_failures += repeat(params, &mut samples, || {
+            pool.install(|| {
+                keypairs
+                    .par_iter()
+                    .zip(&signs)
+                    .filter(|((pk, _), sign)| !verify_msg(pk, sign))
+                    .count()
+            })
+        });
+        time_to_verify_n_parallel.push((threads, samples));
+    }
+
+    //for recovering the pubkey of every node from its sign; a recovered key
+    //that isn't the signer's is counted as a failure
+    let time_to_recover_pubkey = signs
+        .first()
+        .and_then(|sign| scheme.recover_public_key(sign, msg))
+        .map(|_| {
+            let mut samples = params.samples();
+            for ((pk, _), sign) in keypairs.iter().zip(&signs) {
+                let recovered = repeat(params, &mut samples, || {
+                    scheme.recover_public_key(sign, msg)
+                });
+                verify_failures += usize::from(recovered.as_ref() != Some(pk));
+            }
+            samples
+        });
+
+    //for aggregated pubkey
+    let (pks, sks): (Vec<_>, Vec<_>) = keypairs.into_iter().unzip();
+
+    //for batch verification of the first `count` signs; a batch whose result
+    //differs from the individual verifications of its signs is a failure
+    let mut time_to_verify_batch = |count: usize| {
+        let mut samples = params.samples();
+        let valid = repeat(params, &mut samples, || {
+            scheme.verify_batch(&pks[..count], &signs[..count], msg)
+        })?;
+        let expected = individually_valid[..count].iter().all(|&valid| valid);
+        verify_failures += usize::from(valid != expected);
+        Some(samples)
+    };
+    let time_to_verify_fplus1_batch = time_to_verify_batch(fplus1(nodes));
+    let time_to_verify_n_batch = time_to_verify_batch(nodes);
+    //each of two swapped signs fails on its own, so the batch must fail too
+    if nodes > 1 {
+        let mut swapped = signs.clone();
+        swapped.swap(0, 1);
+        if let Some(true) = scheme.verify_batch(&pks, &swapped, msg) {
+            verify_failures += 1;
+        }
+    }
+
+    //for rejecting the proof of a rogue key; an aggregate that takes the
+    //rogue key is a failure too
+    let time_to_reject_rogue_key = scheme.rogue_public_key(&pks).map(|mut rogue| {
+        let mut samples = params.samples();
+        let accepted = repeat(params, &mut samples, || {
+            scheme.verify_possession(&mut rogue)
+        });
+        let aggregated = scheme
+            .aggregate_public_keys(slice::from_ref(&rogue))
+            .is_some();
+        verify_failures += usize::from(accepted) + usize::from(aggregated);
+        samples
+    });
+
+    let mut time_to_aggregate_pubkey = params.samples();
+    let agg_pk = repeat(params, &mut time_to_aggregate_pubkey, || {
+        scheme.aggregate_public_keys(&pks)
+    });
+
+    //for aggregated sign
+    let indexed_signs: Vec<_> = signs.into_iter().enumerate().collect();
+    let mut time_to_aggregate_sign = params.samples();
+    let agg_sign = repeat(params, &mut time_to_aggregate_sign, || {
+        scheme.aggregate_signatures(&indexed_signs)
+    });
+
+    //for the same aggregates reduced in parallel, on `params.threads`
+    //threads; a parallel aggregate that differs from the sequential one is
+    //counted as a failure
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(params.threads)
+        .build()
+        .unwrap();
+    let mut time_to_aggregate_pubkey_parallel = params.samples();
+    let agg_pk_parallel = repeat(params, &mut time_to_aggregate_pubkey_parallel, || {
+        pool.install(|| scheme.aggregate_public_keys_parallel(&pks))
+    });
+    let mut time_to_aggregate_sign_parallel = params.samples();
+    let agg_sign_parallel = repeat(params, &mut time_to_aggregate_sign_parallel, || {
+        pool.install(|| scheme.aggregate_signatures_parallel(&indexed_signs))
+    });
+    verify_failures += usize::from(agg_pk_parallel.is_some() && agg_pk_parallel != agg_pk)
+        + usize::from(agg_sign_parallel.is_some() && agg_sign_parallel != agg_sign);
+
+    //for verifying aggregated sign
+    let time_to_verify_aggregate = agg_sign.as_ref().map(|agg_sign| {
+        let mut samples = params.samples();
+        let valid = repeat(params, &mut samples, || {
+            scheme.verify_aggregate(agg_pk.as_ref(), agg_sign, msg)
+        });
+        verify_failures += usize::from(!valid);
+        samples
+    });
+
+    //for sustained throughput, cycling through the keys of the committee
+    let throughput = params.throughput.map(|duration| Throughput {
+        threads: params.threads,
+        sign: rate(params, duration, |i| sign_msg(&sks[i % nodes])),
+        verify: rate(params, duration, |i| {
+            verify_msg(&pks[i % nodes], &indexed_signs[i % nodes].1)
+        }),
+        aggregate_sign: agg_sign.as_ref().map(|_| {
+            rate(params, duration, |_| {
+                scheme.aggregate_signatures(&indexed_signs)
+            })
+        }),
+        verify_aggregate: agg_sign.as_ref().map(|agg_sign| {
+            rate(params, duration, |_| {
+                scheme.verify_aggregate(agg_pk.as_ref(), agg_sign, msg)
+            })
+        }),
+    });
+
+    let summarise = |operation, samples: &Samples| {
+        Measurement::from_millis(samples).map_err(|source| BenchError::Summary {
+            scheme: scheme.name(),
+            operation,
+            source,
+        })
+    };
+    //histograms of the measured operations, returned with the report
+    let histograms = RefCell::new(Vec::new());
+    let measure = |operation, samples: &Samples| {
+        histograms.borrow_mut().push(hdr::tagged(
+            &format!("{}/{}", scheme.name(), operation),
+            samples.histogram(),
+        ));
+        summarise(operation, samples)
+    };
+    //for the timings that only exist for some schemes
+    let measure_opt = |operation, samples: Option<Samples>| {
+        samples
+            .map(|samples| measure(operation, &samples))
+            .transpose()
+    };
+
+    //speedup is relative to the pool of a single thread
+    let mut verify_n_parallel = Vec::new();
+    for (threads, samples) in &time_to_verify_n_parallel {
+        histograms.borrow_mut().push(hdr::tagged(
+            &format!("{}/verify n on {} threads", scheme.name(), threads),
+            samples.histogram(),
+        ));
+        let time = summarise("verify n parallel", samples)?;
+        let single_thread = verify_n_parallel
+            .first()
+            .map_or(time.stats.mean, |first: &ParallelVerify| {
+                first.time.stats.mean
+            });
+        verify_n_parallel.push(ParallelVerify {
+            threads: *threads,
+            time,
+            speedup: single_thread / time.stats.mean,
+        });
+    }
+
+    let report = SchemeReport {
+        keygen: measure("keygen", &time_to_keygen)?,
+        prove_possession: measure_opt(
+            "prove possession",
+            proves_possession.then_some(time_to_prove_possession),
+        )?,
+        verify_possession: measure_opt("verify possession", time_to_verify_possession)?,
+        commit_nonces: measure_opt("commit nonces", two_round.then_some(time_to_commit_nonces))?,
+        hash: measure_opt("hash", hash.as_ref().map(|_| time_to_hash))?,
+        prehashed: prehash.is_some(),
+        sign: measure("sign", &time_to_sign)?,
+        verify: measure("verify", &time_to_verify)?,
+        verify_fplus1: measure("verify f+1", &time_to_verify_fplus1)?,
+        verify_n: measure("verify n", &time_to_verify_n)?,
+        verify_fplus1_batch: measure_opt("verify f+1 batch", time_to_verify_fplus1_batch)?,
+        verify_n_batch: measure_opt("verify n batch", time_to_verify_n_batch)?,
+        verify_n_parallel,
+        aggregate_pubkey: measure_opt(
+            "aggregate pubkey",
+            agg_pk.as_ref().map(|_| time_to_aggregate_pubkey),
+        )?,
+        aggregate_sign: measure_opt(
+            "aggregate sign",
+            agg_sign.as_ref().map(|_| time_to_aggregate_sign),
+        )?,
+        aggregate_pubkey_parallel: measure_opt(
+            "aggregate pubkey parallel",
+            agg_pk_parallel
+                .as_ref()
+                .map(|_| time_to_aggregate_pubkey_parallel),
+        )?,
+        aggregate_sign_parallel: measure_opt(
+            "aggregate sign parallel",
+            agg_sign_parallel
+                .as_ref()
+                .map(|_| time_to_aggregate_sign_parallel),
+        )?,
+        verify_aggregate: measure_opt("verify aggregate", time_to_verify_aggregate)?,
+        recover_pubkey: measure_opt("recover pubkey", time_to_recover_pubkey)?,
+        reject_wrong_msg: measure(
+            "reject wrong message",
+            &time_to_reject_wrong_msg.expect("every scheme rejects a wrong message"),
+        )?,
+        reject_wrong_pubkey: measure_opt("reject wrong pubkey", time_to_reject_wrong_pubkey)?,
+        reject_tampered_sign: measure_opt("reject tampered sign", time_to_reject_tampered_sign)?,
+        reject_identity_sign: measure_opt("reject identity sign", time_to_reject_identity_sign)?,
+        reject_forged_proof: measure_opt("reject forged proof", time_to_reject_forged_proof)?,
+        reject_rogue_key: measure_opt("reject rogue key", time_to_reject_rogue_key)?,
+        throughput,
+        verify_failures,
+        histograms: histograms.into_inner(),
+    };
+    report.print(scheme.name());
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blsttc::rand::RngCore;
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+
+    //scheme whose signs, proofs and rogue keys are random nonces; it records
+    //every sign and proof it is asked to check
+    #[derive(Default)]
+    struct Recorder {
+        checked: Mutex<BTreeSet<(&'static str, u64)>>,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Key {
+        key: u64,
+        proof: Option<u64>,
+    }
+
+    fn draw() -> u64 {
+        rng::with_rng(|rng| rng.next_u64())
+    }
+
+    impl SignatureScheme for Recorder {
+        type PublicKey = Key;
+        type SecretKey = u64;
+        type Signature = (u64, u64);
+        type AggregatePublicKey = ();
+        type AggregateSignature = ();
+        type MessageHash = ();
+
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn keygen(&mut self, nodes: usize) -> Vec<(Key, u64)> {
+            (0..nodes)
+                .map(|_| {
+                    let key = draw();
+                    (Key { key, proof: None }, key)
+                })
+                .collect()
+        }
+
+        fn prove_possession(&self, pk: &mut Key, _sk: &u64) -> bool {
+            pk.proof = Some(draw());
+            true
+        }
+
+        fn verify_possession(&self, pk: &mut Key) -> bool {
+            if let Some(proof) = pk.proof {
+                self.checked.lock().unwrap().insert(("proof", proof));
+            }
+            pk.proof.is_some()
+        }
+
+        fn rogue_public_key(&self, _pks: &[Key]) -> Option<Key> {
+            Some(Key {
+                key: draw(),
+                proof: None,
+            })
+        }
+
+        fn sign(&self, sk: &u64, _msg: &[u8]) -> (u64, u64) {
+            (*sk, draw())
+        }
+
+        fn verify(&self, pk: &Key, sig: &(u64, u64), _msg: &[u8]) -> bool {
+            self.checked.lock().unwrap().insert(("sign", sig.1));
+            pk.key == sig.0
+        }
+
+        fn forged_proof_signature(
+            &self,
+            sk: &u64,
+            _sig: &(u64, u64),
+            _msg: &[u8],
+        ) -> Option<(u64, u64)> {
+            Some((sk.wrapping_add(1), draw()))
+        }
+    }
+
+    //what is checked of a run of `warmup` and `iterations` from one seed
+    fn checked(warmup: usize, iterations: usize) -> BTreeSet<(&'static str, u64)> {
+        let params = BenchParams {
+            warmup,
+            iterations,
+            seed: 7,
+            ..BenchParams::default()
+        };
+        let mut scheme = Recorder::default();
+        benchmark_scheme(&mut scheme, 4, &params).unwrap();
+        scheme.checked.into_inner().unwrap()
+    }
+
+    #[test]
+    fn kept_values_ignore_warmup_and_iterations() {
+        let short = checked(0, 1);
+        assert!(short.iter().any(|(kind, _)| *kind == "proof"));
+        assert_eq!(short, checked(3, 5));
+    }
+
+    #[test]
+    fn rejects_runs_without_nodes_or_iterations() {
+        let params = BenchParams::default();
+        let result = benchmark_scheme(&mut Recorder::default(), 0, &params);
+        assert!(matches!(result, Err(BenchError::Params { .. })));
+
+        let params = BenchParams {
+            iterations: 0,
+            ..params
+        };
+        let result = benchmark_scheme(&mut Recorder::default(), 4, &params);
+        assert!(matches!(result, Err(BenchError::Params { .. })));
+    }
+}
@@ -1,228 +1,164 @@
 use blsttc_benchmark::{
-    multisig_bls::benchmark_multisig_bls, multisig_bls_nizk::benchmark_nizk_multisig_bls,
-    simple_bls::benchmark_normal_bls, threshold_bls::benchmark_threshold_bls, ecdsa::benchmark_ecdsa_signs,
+    cli::Cli,
+    config::{Config, Scheme},
+    ecdsa::{benchmark_ecdsa_p256, benchmark_ecdsa_secp256k1},
+    ed25519::benchmark_ed25519_signs,
+    frost::benchmark_frost,
+    multisig_bls::benchmark_multisig_bls,
+    multisig_bls_nizk::benchmark_nizk_multisig_bls,
+    multisig_bls_pop::benchmark_pop_multisig_bls,
+    musig2::benchmark_musig2,
+    output::ReportWriter,
+    report::CsvReport,
+    schnorr::benchmark_schnorr_signs,
+    simple_bls::benchmark_normal_bls,
+    threshold_bls::benchmark_threshold_bls,
+    BenchError, BenchParams,
 };
-use csv::Writer;
-use std::fs::File;
-
-fn main() {
-    //array for nodes for benchmark
-    let nodes_info = [10, 20, 50, 100, 150, 200, 250, 500, 1000];
-
-    //file to store bls simple sig results
-    let bls_simple_file = File::create("simple.csv").unwrap();
-
-    //file to store bls threshold sig results
-    let bls_threshold_file = File::create("threshold.csv").unwrap();
-
-    //file to store bls multisig aggregation results
-    let bls_multisig_file = File::create("multisig_aggregation.csv").unwrap();
-
-    //file to store bls multisig aggregation results (nizk)
-    let bls_nizk_multisig_file = File::create("multisig_aggregation_with_nizk.csv").unwrap();
-
-    let ecdsa_sig_file = File::create("ecdsa.csv").unwrap();
-
-    //dedicated writer to each file
-    let mut wtr1 = Writer::from_writer(bls_simple_file);
-    let mut wtr2 = Writer::from_writer(bls_threshold_file);
-    let mut wtr3 = Writer::from_writer(bls_multisig_file);
-    let mut wtr4 = Writer::from_writer(bls_nizk_multisig_file);
-    let mut wtr5 = Writer::from_writer(ecdsa_sig_file);
-
-    //setting coloumn name
-    wtr1.write_record(&[
-        "nodes",
-        "creation_single_sign_g1",
-        "creation_single_sign_g2",
-        "verify_single_sign_g1",
-        "verify_single_sign_g2",
-        "verify_n_signs_g1",
-        "verify_n_signs_g2",
-    ])
-    .unwrap();
-    wtr2.write_record(&[
-        "nodes",
-        "creation_single_sign_share_g1",
-        "creation_single_sign_share_g2",
-        "verify_single_sign_share_g1",
-        "verify_single_sign_share_g2",
-        "verify_n_signshares_g1",
-        "verify_n_signshares_g2",
-        "creation_threshold_sig_g1",
-        "creation_threshold_sig_g2",
-        "verify_threshold_sig_g1",
-        "verify_threshold_sig_g2",
-    ])
-    .unwrap();
-    wtr3.write_record(&[
-        "nodes",
-        "creation_single_sign_share_g1",
-        "creation_single_sign_share_g2",
-        "verify_single_sign_share_g1",
-        "verify_single_sign_share_g2",
-        "verify_n_signshares_g1",
-        "verify_n_signshares_g2",
-        "creation_agg_pubkey_g1",
-        "creation_agg_pubkey_g2",
-        "creation_agg_sign_g1",
-        "creation_agg_sign_g2",
-        "verify_agg_sig_g1",
-        "verify_agg_sig_g2",
-    ])
-    .unwrap();
-    wtr4.write_record(&[
-        "nodes",
-        "creation_single_sign_share_g1",
-        "verify_single_sign_share_g1",
-        "verify_n_signshares_g1",
-        "creation_agg_pubkey_g2",
-        "creation_agg_sign_g1",
-        "verify_agg_sig_g1",
-    ])
-    .unwrap();
-    wtr5.write_record(&[
-        "nodes",
-        "creation_single_sign",
-        "verify_single_sign",
-        "verify_n_signs",
-    ])
-    .unwrap();
-
-    //bls simple signs
-    for nodes in nodes_info {
-        println!("\n ####### nodes : {} ####### \n", nodes);
+use clap::Parser;
+use serde::Serialize;
+use std::fs;
+use std::process;
+use std::time::Instant;
+
+//what went wrong over the runs of a sweep
+#[derive(Default)]
+struct SweepOutcome {
+    verify_failures: usize,
+    //runs that produced no report
+    errors: usize,
+}
 
-        println!("\n ####### simple bls ####### \n");
-        let (
-            mean_single_sign_creation_g1,
-            mean_single_sign_creation_g2,
-            mean_single_sign_verify_g1,
-            mean_single_sign_verify_g2,
-            n_sign_verify_g1,
-            n_sign_verify_g2,
-        ) = benchmark_normal_bls(nodes);
-        wtr1.write_record(&[
-            &format!("{}", nodes),
-            &format!("{:.4}", mean_single_sign_creation_g1),
-            &format!("{:.4}", mean_single_sign_creation_g2),
-            &format!("{:.4}", mean_single_sign_verify_g1),
-            &format!("{:.4}", mean_single_sign_verify_g2),
-            &format!("{:.4}", n_sign_verify_g1),
-            &format!("{:.4}", n_sign_verify_g2),
-        ])
-        .unwrap()
+//runs `benchmark` for every message size and node count of the config and
+//writes one report per run; a run that fails is reported and skipped
+fn run_sweep<R: CsvReport + Serialize>(
+    config: &Config,
+    scheme: Scheme,
+    title: &str,
+    benchmark: impl Fn(usize, &BenchParams) -> Result<R, BenchError>,
+) -> SweepOutcome {
+    //dedicated writer to each scheme
+    let mut wtr = ReportWriter::create(&config.out_dir, scheme.file_stem(), &config.formats);
+    let mut outcome = SweepOutcome::default();
+
+    for &message_size in &config.message_sizes {
+        let params = config.bench_params(message_size);
+
+        for nodes in config.node_counts() {
+            println!(
+                "\n ####### nodes : {} , message size : {} ####### \n",
+                nodes, message_size
+            );
+
+            println!("\n ####### {} ####### \n", title);
+            let run_start = Instant::now();
+            match benchmark(nodes, &params) {
+                Ok(report) => {
+                    outcome.verify_failures += report.verify_failures();
+                    wtr.write_histograms(run_start, nodes, message_size, &report.histograms());
+                    wtr.write(report);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "error: nodes {}, message size {}: {}",
+                        nodes, message_size, e
+                    );
+                    outcome.errors += 1;
+                }
+            }
+        }
     }
+    wtr.finish();
 
-    //bls threshold signs
-    for nodes in nodes_info {
-        println!("\n ####### nodes : {} #######", nodes);
+    outcome
+}
 
-        println!("\n ####### threshold bls ####### \n");
-        let (
-            mean_single_sign_creation_g1,
-            mean_single_sign_creation_g2,
-            mean_single_sign_verify_g1,
-            mean_single_sign_verify_g2,
-            n_sign_verify_g1,
-            n_sign_verify_g2,
-            creation_threshold_sign_g1,
-            creation_threshold_sign_g2,
-            verify_threshold_sign_g1,
-            verify_threshold_sign_g2,
-        ) = benchmark_threshold_bls(nodes);
-        wtr2.write_record(&[
-            &format!("{}", nodes),
-            &format!("{:.4}", mean_single_sign_creation_g1),
-            &format!("{:.4}", mean_single_sign_creation_g2),
-            &format!("{:.4}", mean_single_sign_verify_g1),
-            &format!("{:.4}", mean_single_sign_verify_g2),
-            &format!("{:.4}", n_sign_verify_g1),
-            &format!("{:.4}", n_sign_verify_g2),
-            &format!("{:.4}", creation_threshold_sign_g1),
-            &format!("{:.4}", creation_threshold_sign_g2),
-            &format!("{:.4}", verify_threshold_sign_g1),
-            &format!("{:.4}", verify_threshold_sign_g2),
-        ])
-        .unwrap()
+fn main() {
+    let mut config = Cli::parse().config().unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(2);
+    });
+    let seed = config.resolve_seed();
+    println!("seed : {} (rerun with `--seed {}`)", seed, seed);
+    let groups = &config.groups;
+
+    fs::create_dir_all(&config.out_dir).unwrap();
+
+    let mut verify_failures = 0;
+    let mut errors = 0;
+    for &scheme in &config.schemes {
+        let outcome = match scheme {
+            //bls simple signs
+            Scheme::Simple => run_sweep(&config, scheme, "simple bls", |nodes, params| {
+                benchmark_normal_bls(nodes, groups, params)
+            }),
+            //bls threshold signs
+            Scheme::Threshold => run_sweep(&config, scheme, "threshold bls", |nodes, params| {
+                let threshold = config.threshold.threshold(nodes);
+                benchmark_threshold_bls(nodes, threshold, groups, params)
+            }),
+            //bls multisig aggregation
+            Scheme::Multisig => run_sweep(
+                &config,
+                scheme,
+                "multisig aggregation bls",
+                |nodes, params| {
+                    benchmark_multisig_bls(nodes, config.multisig_threshold, groups, params)
+                },
+            ),
+            //bls multisig aggregation (nizk)
+            Scheme::Nizk => run_sweep(
+                &config,
+                scheme,
+                "multisig aggregation bls (with nizk)",
+                |nodes, params| {
+                    benchmark_nizk_multisig_bls(nodes, config.multisig_threshold, params)
+                },
+            ),
+            //bls multisig aggregation of independent keys (proof of possession)
+            Scheme::MultisigPop => run_sweep(
+                &config,
+                scheme,
+                "multisig aggregation bls (with proof of possession)",
+                |nodes, params| benchmark_pop_multisig_bls(nodes, groups, params),
+            ),
+            Scheme::Ed25519 => run_sweep(&config, scheme, "ed25519 sigs", benchmark_ed25519_signs),
+            Scheme::Secp256k1 => run_sweep(
+                &config,
+                scheme,
+                "ecdsa secp256k1 sigs",
+                benchmark_ecdsa_secp256k1,
+            ),
+            Scheme::P256 => run_sweep(&config, scheme, "ecdsa p-256 sigs", benchmark_ecdsa_p256),
+            Scheme::Schnorr => run_sweep(
+                &config,
+                scheme,
+                "bip-340 schnorr sigs",
+                benchmark_schnorr_signs,
+            ),
+            //schnorr multisig aggregation, two rounds
+            Scheme::Musig2 => run_sweep(&config, scheme, "musig2", benchmark_musig2),
+            //schnorr threshold signs, two rounds
+            Scheme::Frost => run_sweep(&config, scheme, "frost ed25519", |nodes, params| {
+                let threshold = config.threshold.threshold(nodes);
+                benchmark_frost(nodes, threshold, params)
+            }),
+        };
+        verify_failures += outcome.verify_failures;
+        errors += outcome.errors;
     }
 
-    //bls multisig aggregation
-    for nodes in nodes_info {
-        println!("\n ####### nodes : {} ####### \n", nodes);
-
-        println!("\n ####### multisig aggregation bls ####### \n");
-        let (
-            mean_single_sign_creation_g1,
-            mean_single_sign_creation_g2,
-            mean_single_sign_verify_g1,
-            mean_single_sign_verify_g2,
-            n_sign_verify_g1,
-            n_sign_verify_g2,
-            creation_agg_pubkey_g1,
-            creation_agg_pubkey_g2,
-            creation_agg_sign_g1,
-            creation_agg_sign_g2,
-            verify_agg_sign_g1,
-            verify_agg_sign_g2,
-        ) = benchmark_multisig_bls(nodes);
-        wtr3.write_record(&[
-            &format!("{}", nodes),
-            &format!("{:.4}", mean_single_sign_creation_g1),
-            &format!("{:.4}", mean_single_sign_creation_g2),
-            &format!("{:.4}", mean_single_sign_verify_g1),
-            &format!("{:.4}", mean_single_sign_verify_g2),
-            &format!("{:.4}", n_sign_verify_g1),
-            &format!("{:.4}", n_sign_verify_g2),
-            &format!("{:.4}", creation_agg_pubkey_g1),
-            &format!("{:.4}", creation_agg_pubkey_g2),
-            &format!("{:.4}", creation_agg_sign_g1),
-            &format!("{:.4}", creation_agg_sign_g2),
-            &format!("{:.4}", verify_agg_sign_g1),
-            &format!("{:.4}", verify_agg_sign_g2),
-        ])
-        .unwrap();
+    //results are still written, but the run is marked as failed
+    if verify_failures > 0 {
+        eprintln!(
+            "error: {} verifications returned the wrong result, see `verify_failures` in the results",
+            verify_failures
+        );
     }
-
-    for nodes in nodes_info {
-        println!("\n ####### nodes : {} ####### \n", nodes);
-
-        println!("\n ####### multisig aggregation bls (with nizk) ####### \n");
-        let (
-            mean_single_sign_creation_g1,
-            mean_single_sign_verify_g1,
-            n_sign_verify_g1,
-            creation_agg_pubkey_g2,
-            creation_agg_sign_g1,
-            verify_agg_sign_g1,
-        ) = benchmark_nizk_multisig_bls(nodes);
-        wtr4.write_record(&[
-            &format!("{}", nodes),
-            &format!("{:.4}", mean_single_sign_creation_g1),
-            &format!("{:.4}", mean_single_sign_verify_g1),
-            &format!("{:.4}", n_sign_verify_g1),
-            &format!("{:.4}", creation_agg_pubkey_g2),
-            &format!("{:.4}", creation_agg_sign_g1),
-            &format!("{:.4}", verify_agg_sign_g1),
-        ])
-        .unwrap();
+    if errors > 0 {
+        eprintln!("error: {} runs produced no results", errors);
     }
-
-    for nodes in nodes_info {
-        println!("\n ####### nodes : {} ####### \n", nodes);
-
-        println!("\n ####### ecdsa sigs ####### \n");
-        let (
-            mean_single_sign_creation,
-            mean_single_sign_verify,
-            n_sign_verify,
-        ) = benchmark_ecdsa_signs(nodes);
-        wtr5.write_record(&[
-            &format!("{}", nodes),
-            &format!("{:.4}", mean_single_sign_creation),
-            &format!("{:.4}", mean_single_sign_verify),
-            &format!("{:.4}", n_sign_verify),
-        ])
-        .unwrap();
+    if verify_failures > 0 || errors > 0 {
+        process::exit(1);
     }
 }
@@ -1,356 +1,454 @@
-use super::stats::{calculate_mean, calculate_median};
+use super::forgery::{tampered_g1, tampered_g2};
+use super::report::{group_outcome_cells, CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::threshold_bls::deal_shares;
+use super::{benchmark_scheme, BenchError, BenchParams, Group, SignatureScheme};
+use blsttc::group::prime::PrimeCurveAffine;
+use blsttc::group::{Curve, Group as _};
 use blsttc::{
-    G1Affine, G1Projective, G2Affine, G2Projective, PublicKeyG1, PublicKeyG2, PublicKeyShareG1,
-    PublicKeyShareG2, SecretKeySet, SecretKeyShare, SignatureG1, SignatureG2, SignatureShareG1,
-    SignatureShareG2,
+    hash_g1, hash_g2, G1Affine, G2Affine, PublicKeyG1, PublicKeyG2, PublicKeyShareG1,
+    PublicKeyShareG2, SecretKeyShare, SignatureG1, SignatureG2, SignatureShareG1, SignatureShareG2,
 };
-use std::time::Instant;
+use rayon::prelude::*;
+use serde::Serialize;
 
-pub fn benchmark_multisig_bls(
-    nodes: usize,
-) -> (f64, f64, f64, f64, f64, f64, f64, f64, f64, f64, f64, f64) {
-    let mut keypairs: Vec<(PublicKeyShareG1, PublicKeyShareG2, SecretKeyShare)> = Vec::new();
-    let msg = "hello, this is benchmark".as_bytes();
-    let mut signs_g1 = Vec::new();
-    let mut signs_g2 = Vec::new();
-
-    let threshold = 2; // some random value less than total nodes
-    let mut rng = blsttc::rand::rngs::OsRng;
-    // Generate a set of secret key shares
-    let sk_set = SecretKeySet::random(threshold, &mut rng);
-    // Get the corresponding public key set
-    let pk_set_g1 = sk_set.public_keys();
-    let pk_set_g2 = sk_set.public_keys_g2();
-
-    for node in 0..nodes {
-        let sk_share = sk_set.secret_key_share(node);
-        let pk_share_g1 = pk_set_g1.public_key_share(node);
-        let pk_share_g2 = pk_set_g2.public_key_share(node);
-
-        keypairs.push((pk_share_g1, pk_share_g2, sk_share));
+//multisig sign shares in g1, aggregated and verified against the
+//aggregated pubkey in g2
+pub struct MultisigBlsG1 {
+    pub threshold: usize,
+}
+
+impl SignatureScheme for MultisigBlsG1 {
+    type PublicKey = PublicKeyShareG2;
+    type SecretKey = SecretKeyShare;
+    type Signature = SignatureShareG1;
+    type AggregatePublicKey = PublicKeyShareG2;
+    type AggregateSignature = SignatureShareG1;
+    type MessageHash = G1Affine;
+
+    fn name(&self) -> &'static str {
+        "bls sign share in g1"
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn keygen(&mut self, nodes: usize) -> Vec<(PublicKeyShareG2, SecretKeyShare)> {
+        let (sk_set, sk_shares) = deal_shares(self.threshold, nodes);
+        // Get the corresponding public key set
+        let pk_set = sk_set.public_keys_g2();
+        sk_shares
+            .into_iter()
+            .enumerate()
+            .map(|(node, sk_share)| (pk_set.public_key_share(node), sk_share))
+            .collect()
+    }
+
+    fn sign(&self, sk: &SecretKeyShare, msg: &[u8]) -> SignatureShareG1 {
+        sk.sign_g1(msg)
+    }
 
-    //for creating sign share in g1
-    let mut time_to_create_sign_share_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-        let sign = keypairs[i].2.sign_g1(msg);
-        let end_time = Instant::now();
-        signs_g1.push(sign);
+    fn verify(&self, pk: &PublicKeyShareG2, sig: &SignatureShareG1, msg: &[u8]) -> bool {
+        pk.verify(sig, msg)
+    }
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_sign_share_g1.push(elapsed_time.as_secs_f64());
+    fn hash_message(&self, msg: &[u8]) -> Option<G1Affine> {
+        Some(hash_g1(msg))
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn sign_hashed(&self, sk: &SecretKeyShare, hash: &G1Affine) -> Option<SignatureShareG1> {
+        Some(SignatureShareG1(sk.0.sign_g1(*hash)))
+    }
 
-    //for creating sign share in g2
-    let mut time_to_create_sign_share_g2 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-        let sign = keypairs[i].2.sign_g2(msg);
-        let end_time = Instant::now();
-        signs_g2.push(sign);
+    fn verify_hashed(
+        &self,
+        pk: &PublicKeyShareG2,
+        sig: &SignatureShareG1,
+        hash: &G1Affine,
+    ) -> Option<bool> {
+        Some(pk.0.verify_g1(&sig.0, *hash))
+    }
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_sign_share_g2.push(elapsed_time.as_secs_f64());
+    fn tampered_signature(&self, sig: &SignatureShareG1) -> Option<SignatureShareG1> {
+        Some(SignatureShareG1(SignatureG1(tampered_g1(&sig.0 .0))))
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn identity_signature(&self, _sig: &SignatureShareG1) -> Option<SignatureShareG1> {
+        Some(SignatureShareG1(SignatureG1(G1Affine::identity())))
+    }
 
-    //for verifying single g1 sign share
-    let mut time_to_verify_single_sign_share_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-        keypairs[i].1.verify(&signs_g1[i], msg);
-        let end_time = Instant::now();
+    fn aggregate_public_keys(&self, pks: &[PublicKeyShareG2]) -> Option<PublicKeyShareG2> {
+        Some(aggregate_pubkey_g2(pks, |pk| pk, Reduction::Sequential))
+    }
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_single_sign_share_g1.push(elapsed_time.as_secs_f64());
+    fn aggregate_public_keys_parallel(&self, pks: &[PublicKeyShareG2]) -> Option<PublicKeyShareG2> {
+        Some(aggregate_pubkey_g2(pks, |pk| pk, Reduction::Parallel))
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn aggregate_signatures(&self, sigs: &[(usize, SignatureShareG1)]) -> Option<SignatureShareG1> {
+        Some(aggregate_sign_g1(
+            sigs,
+            |(_, sig)| sig,
+            Reduction::Sequential,
+        ))
+    }
 
-    //for verifying single g2 sign share
-    let mut time_to_verify_single_sign_share_g2 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-        keypairs[i].0.verify(&signs_g2[i], msg);
-        let end_time = Instant::now();
+    fn aggregate_signatures_parallel(
+        &self,
+        sigs: &[(usize, SignatureShareG1)],
+    ) -> Option<SignatureShareG1> {
+        Some(aggregate_sign_g1(sigs, |(_, sig)| sig, Reduction::Parallel))
+    }
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_single_sign_share_g2.push(elapsed_time.as_secs_f64());
+    fn verify_aggregate(
+        &self,
+        agg_pk: Option<&PublicKeyShareG2>,
+        agg_sig: &SignatureShareG1,
+        msg: &[u8],
+    ) -> bool {
+        agg_pk.is_some_and(|agg_pk| agg_pk.verify(agg_sig, msg))
     }
+}
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+//multisig sign shares in g2, aggregated and verified against the
+//aggregated pubkey in g1
+pub struct MultisigBlsG2 {
+    pub threshold: usize,
+}
 
-    //for all sign share in g1
-    let mut time_to_verify_all_independent_signs_g1 = 0.0;
+impl SignatureScheme for MultisigBlsG2 {
+    type PublicKey = PublicKeyShareG1;
+    type SecretKey = SecretKeyShare;
+    type Signature = SignatureShareG2;
+    type AggregatePublicKey = PublicKeyShareG1;
+    type AggregateSignature = SignatureShareG2;
+    type MessageHash = G2Affine;
 
-    let start_time = Instant::now();
-    for i in 0..nodes {
-        keypairs[i].1.verify(&signs_g1[i], msg);
+    fn name(&self) -> &'static str {
+        "bls sign share in g2"
     }
-    let end_time = Instant::now();
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs_g1 = elapsed_time.as_secs_f64();
+    fn keygen(&mut self, nodes: usize) -> Vec<(PublicKeyShareG1, SecretKeyShare)> {
+        let (sk_set, sk_shares) = deal_shares(self.threshold, nodes);
+        // Get the corresponding public key set
+        let pk_set = sk_set.public_keys();
+        sk_shares
+            .into_iter()
+            .enumerate()
+            .map(|(node, sk_share)| (pk_set.public_key_share(node), sk_share))
+            .collect()
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn sign(&self, sk: &SecretKeyShare, msg: &[u8]) -> SignatureShareG2 {
+        sk.sign_g2(msg)
+    }
 
-    //for all sign share in g2
-    let mut time_to_verify_all_independent_signs_g2 = 0.0;
+    fn verify(&self, pk: &PublicKeyShareG1, sig: &SignatureShareG2, msg: &[u8]) -> bool {
+        pk.verify(sig, msg)
+    }
 
-    let start_time = Instant::now();
-    for i in 0..nodes {
-        keypairs[i].0.verify(&signs_g2[i], msg);
+    fn hash_message(&self, msg: &[u8]) -> Option<G2Affine> {
+        Some(hash_g2(msg))
     }
-    let end_time = Instant::now();
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs_g2 = elapsed_time.as_secs_f64();
+    fn sign_hashed(&self, sk: &SecretKeyShare, hash: &G2Affine) -> Option<SignatureShareG2> {
+        Some(SignatureShareG2(sk.0.sign_g2(*hash)))
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn verify_hashed(
+        &self,
+        pk: &PublicKeyShareG1,
+        sig: &SignatureShareG2,
+        hash: &G2Affine,
+    ) -> Option<bool> {
+        Some(pk.0.verify_g2(&sig.0, *hash))
+    }
 
-    //for creating aggregated sign g1
-    let mut time_to_create_aggregated_sign_g1 = 0.0;
+    fn tampered_signature(&self, sig: &SignatureShareG2) -> Option<SignatureShareG2> {
+        Some(SignatureShareG2(SignatureG2(tampered_g2(&sig.0 .0))))
+    }
 
-    let start_time = Instant::now();
-    let mut agg_sign_g1 = signs_g1[0].clone();
-    for i in 1..signs_g1.len() {
-        let new_agg_sign_g1 = aggregate_sign_g1(&agg_sign_g1, &signs_g1[i]);
-        agg_sign_g1 = new_agg_sign_g1;
+    fn identity_signature(&self, _sig: &SignatureShareG2) -> Option<SignatureShareG2> {
+        Some(SignatureShareG2(SignatureG2(G2Affine::identity())))
     }
-    let end_time = Instant::now();
 
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_aggregated_sign_g1 = elapsed_time.as_secs_f64();
+    fn aggregate_public_keys(&self, pks: &[PublicKeyShareG1]) -> Option<PublicKeyShareG1> {
+        Some(aggregate_pubkey_g1(pks, |pk| pk, Reduction::Sequential))
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn aggregate_public_keys_parallel(&self, pks: &[PublicKeyShareG1]) -> Option<PublicKeyShareG1> {
+        Some(aggregate_pubkey_g1(pks, |pk| pk, Reduction::Parallel))
+    }
 
-    //for creating aggregated sign g2
-    let mut time_to_create_aggregated_sign_g2 = 0.0;
+    fn aggregate_signatures(&self, sigs: &[(usize, SignatureShareG2)]) -> Option<SignatureShareG2> {
+        Some(aggregate_sign_g2(
+            sigs,
+            |(_, sig)| sig,
+            Reduction::Sequential,
+        ))
+    }
 
-    let start_time = Instant::now();
-    let mut agg_sign_g2 = signs_g2[0].clone();
-    for i in 1..signs_g2.len() {
-        let new_agg_sign_g2 = aggregate_sign_g2(&agg_sign_g2, &signs_g2[i]);
-        agg_sign_g2 = new_agg_sign_g2;
+    fn aggregate_signatures_parallel(
+        &self,
+        sigs: &[(usize, SignatureShareG2)],
+    ) -> Option<SignatureShareG2> {
+        Some(aggregate_sign_g2(sigs, |(_, sig)| sig, Reduction::Parallel))
     }
-    let end_time = Instant::now();
 
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_aggregated_sign_g2 = elapsed_time.as_secs_f64();
+    fn verify_aggregate(
+        &self,
+        agg_pk: Option<&PublicKeyShareG1>,
+        agg_sig: &SignatureShareG2,
+        msg: &[u8],
+    ) -> bool {
+        agg_pk.is_some_and(|agg_pk| agg_pk.verify(agg_sig, msg))
+    }
+}
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+//reports are keyed by the group of the signatures; the aggregated pubkey
+//lives in the opposite group
+#[derive(Clone, Debug, Serialize)]
+pub struct MultisigBlsReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub g1: Option<SchemeReport>,
+    pub g2: Option<SchemeReport>,
+}
 
-    //for creating aggregated pubkey g1
-    let mut time_to_create_aggregated_pubkey_g1 = 0.0;
+impl CsvReport for MultisigBlsReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            (
+                "hash_to_curve_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.hash)),
+            ),
+            (
+                "hash_to_curve_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.hash)),
+            ),
+            (
+                "creation_single_sign_share_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.sign)),
+            ),
+            (
+                "creation_single_sign_share_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.sign)),
+            ),
+            (
+                "verify_single_sign_share_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify)),
+            ),
+            (
+                "verify_single_sign_share_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify)),
+            ),
+            (
+                "verify_n_signshares_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify_n)),
+            ),
+            (
+                "verify_n_signshares_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify_n)),
+            ),
+            (
+                "verify_n_signshares_parallel_g1",
+                CsvCell::Parallel(
+                    self.g1
+                        .iter()
+                        .flat_map(|g1| g1.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+            (
+                "verify_n_signshares_parallel_g2",
+                CsvCell::Parallel(
+                    self.g2
+                        .iter()
+                        .flat_map(|g2| g2.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+        ]);
+        cells.extend(aggregation_cells(&self.g1, &self.g2));
+        cells.extend(group_outcome_cells(&self.g1, &self.g2));
+        cells
+    }
 
-    let start_time = Instant::now();
-    let mut agg_pubkey_g1 = keypairs[0].0.clone();
-    for i in 1..keypairs.len() {
-        let new_agg_pubkey_g1 = aggregate_pubkey_g1(&agg_pubkey_g1, &keypairs[i].0);
-        agg_pubkey_g1 = new_agg_pubkey_g1;
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        [&self.g1, &self.g2].into_iter().flatten().collect()
     }
-    let end_time = Instant::now();
+}
 
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_aggregated_pubkey_g1 = elapsed_time.as_secs_f64();
+//aggregation and rejection cells of the multisig reports, whose keys are
+//aggregated in the group opposite to the signs
+pub fn aggregation_cells(
+    g1: &Option<SchemeReport>,
+    g2: &Option<SchemeReport>,
+) -> [(&'static str, CsvCell); 18] {
+    [
+        //pubkey in g1 is aggregated to verify signatures in g2
+        (
+            "creation_agg_pubkey_g1",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.aggregate_pubkey)),
+        ),
+        (
+            "creation_agg_pubkey_g2",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.aggregate_pubkey)),
+        ),
+        (
+            "creation_agg_pubkey_parallel_g1",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.aggregate_pubkey_parallel)),
+        ),
+        (
+            "creation_agg_pubkey_parallel_g2",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.aggregate_pubkey_parallel)),
+        ),
+        (
+            "creation_agg_sign_g1",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.aggregate_sign)),
+        ),
+        (
+            "creation_agg_sign_g2",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.aggregate_sign)),
+        ),
+        (
+            "creation_agg_sign_parallel_g1",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.aggregate_sign_parallel)),
+        ),
+        (
+            "creation_agg_sign_parallel_g2",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.aggregate_sign_parallel)),
+        ),
+        (
+            "verify_agg_sig_g1",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.verify_aggregate)),
+        ),
+        (
+            "verify_agg_sig_g2",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.verify_aggregate)),
+        ),
+        (
+            "reject_wrong_msg_g1",
+            CsvCell::Timing(g1.as_ref().map(|g1| g1.reject_wrong_msg)),
+        ),
+        (
+            "reject_wrong_msg_g2",
+            CsvCell::Timing(g2.as_ref().map(|g2| g2.reject_wrong_msg)),
+        ),
+        (
+            "reject_wrong_pubkey_g1",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.reject_wrong_pubkey)),
+        ),
+        (
+            "reject_wrong_pubkey_g2",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.reject_wrong_pubkey)),
+        ),
+        (
+            "reject_tampered_sig_g1",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.reject_tampered_sign)),
+        ),
+        (
+            "reject_tampered_sig_g2",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.reject_tampered_sign)),
+        ),
+        (
+            "reject_identity_sig_g1",
+            CsvCell::Timing(g1.as_ref().and_then(|g1| g1.reject_identity_sign)),
+        ),
+        (
+            "reject_identity_sig_g2",
+            CsvCell::Timing(g2.as_ref().and_then(|g2| g2.reject_identity_sign)),
+        ),
+    ]
+}
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+//`threshold` only shapes the key set the shares are dealt from, any value
+//less than the number of nodes works
+pub fn benchmark_multisig_bls(
+    nodes: usize,
+    threshold: usize,
+    groups: &[Group],
+    params: &BenchParams,
+) -> Result<MultisigBlsReport, BenchError> {
+    let g1 = groups
+        .contains(&Group::G1)
+        .then(|| benchmark_scheme(&mut MultisigBlsG1 { threshold }, nodes, params))
+        .transpose()?;
+    let g2 = groups
+        .contains(&Group::G2)
+        .then(|| benchmark_scheme(&mut MultisigBlsG2 { threshold }, nodes, params))
+        .transpose()?;
+
+    Ok(MultisigBlsReport {
+        run: RunInfo::new(nodes, params),
+        g1,
+        g2,
+    })
+}
 
-    //for creating aggregated pubkey g2
-    let mut time_to_create_aggregated_pubkey_g2 = 0.0;
+//how the points of a slice are summed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    //folded left to right on the calling thread
+    Sequential,
+    //reduced as a binary tree across the threads of the current rayon pool
+    Parallel,
+}
 
-    let start_time = Instant::now();
-    let mut agg_pubkey_g2 = keypairs[0].1.clone();
-    for i in 1..keypairs.len() {
-        let new_agg_pubkey_g2 = aggregate_pubkey_g2(&agg_pubkey_g2, &keypairs[i].1);
-        agg_pubkey_g2 = new_agg_pubkey_g2;
+//sum of the points of `items`, as taken out by `point`; the sequential sum
+//adds one point at a time and converts back to affine after every step, as
+//the pairwise aggregation always has, while the parallel one is kept in
+//projective form and only converted to affine at the end
+pub fn sum_points<T: Sync, P: PrimeCurveAffine>(
+    items: &[T],
+    point: impl Fn(&T) -> P + Sync + Send,
+    reduction: Reduction,
+) -> P {
+    match reduction {
+        Reduction::Sequential => {
+            let mut points = items.iter().map(point);
+            let first = points.next().unwrap_or_else(P::identity);
+            points.fold(first, |sum, point| (point.to_curve() + sum).to_affine())
+        }
+        Reduction::Parallel => items
+            .par_iter()
+            .map(|item| point(item).to_curve())
+            .reduce(P::Curve::identity, |left, right| left + right)
+            .to_affine(),
     }
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_aggregated_pubkey_g2 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying aggregated sign g1
-    let mut time_to_verify_aggregated_sign_g1 = 0.0;
-
-    let start_time = Instant::now();
-    agg_pubkey_g2.verify(&agg_sign_g1, msg);
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_aggregated_sign_g1 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying aggregated sign g2
-    let mut time_to_verify_aggregated_sign_g2 = 0.0;
-
-    let start_time = Instant::now();
-    agg_pubkey_g1.verify(&agg_sign_g2, msg);
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_aggregated_sign_g2 = elapsed_time.as_secs_f64();
-
-    let mean_single_sign_creation_g1 = calculate_mean(&time_to_create_sign_share_g1) * 1000.0;
-    let median_single_sign_creation_g1 =
-        calculate_median(&mut time_to_create_sign_share_g1) * 1000.0;
-    let mean_single_sign_verify_g1 = calculate_mean(&time_to_verify_single_sign_share_g1) * 1000.0;
-    let median_single_sign_verify_g1 =
-        calculate_median(&mut time_to_verify_single_sign_share_g1) * 1000.0;
-    let n_sign_verify_g1 = time_to_verify_all_independent_signs_g1 * 1000.0;
-    let creation_agg_pubkey_g2 = time_to_create_aggregated_pubkey_g2 * 1000.0;
-    let creation_agg_sign_g1 = time_to_create_aggregated_sign_g1 * 1000.0;
-    let verify_agg_sign_g1 = time_to_verify_aggregated_sign_g1 * 1000.0;
-
-    let mean_single_sign_creation_g2 = calculate_mean(&time_to_create_sign_share_g2) * 1000.0;
-    let median_single_sign_creation_g2 =
-        calculate_median(&mut time_to_create_sign_share_g2) * 1000.0;
-    let mean_single_sign_verify_g2 = calculate_mean(&time_to_verify_single_sign_share_g2) * 1000.0;
-    let median_single_sign_verify_g2 =
-        calculate_median(&mut time_to_verify_single_sign_share_g2) * 1000.0;
-    let n_sign_verify_g2 = time_to_verify_all_independent_signs_g2 * 1000.0;
-    let creation_agg_pubkey_g1 = time_to_create_aggregated_pubkey_g1 * 1000.0;
-    let creation_agg_sign_g2 = time_to_create_aggregated_sign_g2 * 1000.0;
-    let verify_agg_sign_g2 = time_to_verify_aggregated_sign_g2 * 1000.0;
-
-    //OUTPUT
-    println!("for signshare in g1 : ");
-    println!(
-        "mean time to create single  bls sign share in g1: {:.4} ms",
-        mean_single_sign_creation_g1
-    );
-    println!(
-        "median time to create single bls sign share in g1: {:.4} ms",
-        median_single_sign_creation_g1
-    );
-    println!(
-        "mean time to verify single bls sign share in g1: {:.4} ms",
-        mean_single_sign_verify_g1
-    );
-    println!(
-        "meadian time to verify single bls sign share in g1: {:.4} ms",
-        median_single_sign_verify_g1
-    );
-    println!(
-        "time takes to verify n independent signs share in g1: {:.4} ms",
-        n_sign_verify_g1
-    );
-    println!(
-        "time takes to create aggregated pubkey in g2: {:.4} ms",
-        creation_agg_pubkey_g2
-    );
-    println!(
-        "time takes to create aggregated sign in g1: {:.4} ms",
-        creation_agg_sign_g1
-    );
-    println!(
-        "time takes to verify aggregated sign in g1: {:.4} ms",
-        verify_agg_sign_g1
-    );
-    println!("");
-
-    println!("for signshare in g2 : ");
-    println!(
-        "mean time to create single  bls sign share in g2: {:.4} ms",
-        mean_single_sign_creation_g2
-    );
-    println!(
-        "median time to create single bls sign share in g2: {:.4} ms",
-        median_single_sign_creation_g2
-    );
-    println!(
-        "mean time to verify single bls sign share in g2: {:.4} ms",
-        mean_single_sign_verify_g2
-    );
-    println!(
-        "meadian time to verify single bls sign share in g2: {:.4} ms",
-        median_single_sign_verify_g2
-    );
-    println!(
-        "time takes to verify n independent signs share in g2: {:.4} ms",
-        n_sign_verify_g2
-    );
-    println!(
-        "time takes to create aggregated pubkey in g1: {:.4} ms",
-        creation_agg_pubkey_g1
-    );
-    println!(
-        "time takes to create aggregated sign in g2: {:.4} ms",
-        creation_agg_sign_g2
-    );
-    println!(
-        "time takes to verify aggregated sign in g2: {:.4} ms",
-        verify_agg_sign_g2
-    );
-    println!("");
-
-    (
-        mean_single_sign_creation_g1,
-        mean_single_sign_creation_g2,
-        mean_single_sign_verify_g1,
-        mean_single_sign_verify_g2,
-        n_sign_verify_g1,
-        n_sign_verify_g2,
-        creation_agg_pubkey_g1,
-        creation_agg_pubkey_g2,
-        creation_agg_sign_g1,
-        creation_agg_sign_g2,
-        verify_agg_sign_g1,
-        verify_agg_sign_g2,
-    )
 }
 
-pub fn aggregate_sign_g1(
-    agg_sig: &SignatureShareG1,
-    new_sign: &SignatureShareG1,
+//the signs of `items`, as taken out by `sign`, aggregated into one
+pub fn aggregate_sign_g1<T: Sync>(
+    items: &[T],
+    sign: impl Fn(&T) -> &SignatureShareG1 + Sync + Send,
+    reduction: Reduction,
 ) -> SignatureShareG1 {
-    let agg_sign = G1Affine::from(agg_sig.0 .0 + G1Projective::from(new_sign.0 .0));
-    let sign = SignatureShareG1(SignatureG1(agg_sign));
-    sign
+    let agg_sign = sum_points(items, |item| sign(item).0 .0, reduction);
+    SignatureShareG1(SignatureG1(agg_sign))
 }
 
-pub fn aggregate_sign_g2(
-    agg_sig: &SignatureShareG2,
-    new_sign: &SignatureShareG2,
+pub fn aggregate_sign_g2<T: Sync>(
+    items: &[T],
+    sign: impl Fn(&T) -> &SignatureShareG2 + Sync + Send,
+    reduction: Reduction,
 ) -> SignatureShareG2 {
-    let agg_sign = G2Affine::from(agg_sig.0 .0 + G2Projective::from(new_sign.0 .0));
-    let sign = SignatureShareG2(SignatureG2(agg_sign));
-    sign
+    let agg_sign = sum_points(items, |item| sign(item).0 .0, reduction);
+    SignatureShareG2(SignatureG2(agg_sign))
 }
 
-pub fn aggregate_pubkey_g1(
-    agg_key: &PublicKeyShareG1,
-    new_key: &PublicKeyShareG1,
+//the pubkeys of `items`, as taken out by `key`, aggregated into one
+pub fn aggregate_pubkey_g1<T: Sync>(
+    items: &[T],
+    key: impl Fn(&T) -> &PublicKeyShareG1 + Sync + Send,
+    reduction: Reduction,
 ) -> PublicKeyShareG1 {
-    let agg_key = G1Affine::from(agg_key.0 .0 + G1Projective::from(new_key.0 .0));
-    let key = PublicKeyShareG1(PublicKeyG1(agg_key));
-    key
+    let agg_key = sum_points(items, |item| key(item).0 .0, reduction);
+    PublicKeyShareG1(PublicKeyG1(agg_key))
 }
 
-pub fn aggregate_pubkey_g2(
-    agg_key: &PublicKeyShareG2,
-    new_key: &PublicKeyShareG2,
+pub fn aggregate_pubkey_g2<T: Sync>(
+    items: &[T],
+    key: impl Fn(&T) -> &PublicKeyShareG2 + Sync + Send,
+    reduction: Reduction,
 ) -> PublicKeyShareG2 {
-    let agg_key = G2Affine::from(agg_key.0 .0 + G2Projective::from(new_key.0 .0));
-    let key = PublicKeyShareG2(PublicKeyG2(agg_key));
-    key
+    let agg_key = sum_points(items, |item| key(item).0 .0, reduction);
+    PublicKeyShareG2(PublicKeyG2(agg_key))
 }
@@ -1,232 +1,290 @@
-use super::stats::{calculate_mean, calculate_median};
+use super::forgery::tampered_g1;
+use super::multisig_bls::{aggregate_pubkey_g2, aggregate_sign_g1, Reduction};
+use super::report::{CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::threshold_bls::deal_shares;
+use super::{benchmark_scheme, BenchError, BenchParams, SignatureScheme};
 use blsttc::group::ff::Field;
 use blsttc::group::prime::PrimeCurveAffine;
 use blsttc::nizk_dleq::{prove_gen, verify_proof, DLEqInstance, DLEqWitness, ZkProofDLEq};
-use blsttc::rand::thread_rng as blsttc_thread_rng;
 use blsttc::{
-    hash_g1, Fr, G1Affine, G1Projective, G2Affine, G2Projective, PublicKeyG2, PublicKeyShareG1,
-    PublicKeyShareG2, SecretKeySet, SecretKeyShare, SignatureG1, SignatureShareG1,
+    hash_g1, Fr, G1Affine, PublicKeyShareG1, PublicKeyShareG2, SecretKeyShare, SignatureG1,
+    SignatureShareG1,
 };
-use std::time::Instant;
+use serde::Serialize;
 
-pub fn benchmark_nizk_multisig_bls(nodes: usize) -> (f64, f64, f64, f64, f64, f64) {
-    let mut keypairs: Vec<(PublicKeyShareG1, PublicKeyShareG2, SecretKeyShare)> = Vec::new();
-    let msg = "hello, this is benchmark".as_bytes();
-    let mut signs_g1 = Vec::new();
-
-    let mut nizks = Vec::new();
-
-    let threshold = 2; // some random value less than total nodes
-    let mut rng = blsttc::rand::rngs::OsRng;
-    // Generate a set of secret key shares
-    let sk_set = SecretKeySet::random(threshold, &mut rng);
-    // Get the corresponding public key set
-    let pk_set_g1 = sk_set.public_keys();
-    let pk_set_g2 = sk_set.public_keys_g2();
-
-    for node in 0..nodes {
-        let sk_share = sk_set.secret_key_share(node);
-        let pk_share_g1 = pk_set_g1.public_key_share(node);
-        let pk_share_g2 = pk_set_g2.public_key_share(node);
-
-        keypairs.push((pk_share_g1, pk_share_g2, sk_share));
-    }
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+//multisig sign shares in g1 where every share carries a nizk proof that it
+//was made with the same key as the signer's g1 pubkey, so shares can be
+//checked without a pairing
+pub struct NizkMultisigBlsG1 {
+    pub threshold: usize,
+}
 
-    //for creating sign share in g1 with nizk
-    let mut time_to_create_sign_share_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-        let sign = keypairs[i].2.sign_g1(msg);
+impl NizkMultisigBlsG1 {
+    //signs `hash` and proves the share against the g1 pubkey
+    fn sign_on(
+        &self,
+        sk: &(PublicKeyShareG1, SecretKeyShare),
+        hash: &G1Affine,
+    ) -> (SignatureShareG1, ZkProofDLEq) {
+        let (pk, sk_share) = sk;
+        let sign = SignatureShareG1(sk_share.0.sign_g1(*hash));
 
         //calc nizk
-        let pk = keypairs[i].0;
-        let mut rng = blsttc_thread_rng();
-        let r: Fr = Fr::random(&mut rng);
+        let r: Fr = with_rng(|rng| Fr::random(rng));
         let g = G1Affine::generator();
-        let h = hash_g1(msg);
+        let h = *hash;
         let g_x = pk.0 .0;
         let h_x = sign.0 .0;
         let instance = DLEqInstance { g, h, g_x, h_x };
         let witness = DLEqWitness {
-            scalar_x: keypairs[i].2 .0 .0,
+            scalar_x: sk_share.0 .0,
             scalar_r: r,
         };
         let nizk = prove_gen(&instance, &witness);
-        let end_time = Instant::now();
-        signs_g1.push(sign);
-        nizks.push(nizk);
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_sign_share_g1.push(elapsed_time.as_secs_f64());
+        (sign, nizk)
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying single g1 sign share with nizk
-    let mut time_to_verify_single_sign_share_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-        let sig = &signs_g1[i];
-        let key = keypairs[i].0;
+    fn verify_on(
+        &self,
+        pk: &(PublicKeyShareG1, PublicKeyShareG2),
+        sig: &(SignatureShareG1, ZkProofDLEq),
+        hash: &G1Affine,
+    ) -> bool {
+        let (key, _) = pk;
+        let (sig, nizk) = sig;
         let g = G1Affine::generator();
         let g_x = key.0 .0;
         let h_x = sig.0 .0;
-        let nizk = &nizks[i];
-        let h = hash_g1(msg);
+        let h = *hash;
         let instance = DLEqInstance { g, h, g_x, h_x };
-        verify_proof(&instance, &nizk).unwrap();
-        let end_time = Instant::now();
+        verify_proof(&instance, nizk).is_ok()
+    }
+}
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_single_sign_share_g1.push(elapsed_time.as_secs_f64());
+impl SignatureScheme for NizkMultisigBlsG1 {
+    //g1 pubkey checks the nizk, g2 pubkey is aggregated
+    type PublicKey = (PublicKeyShareG1, PublicKeyShareG2);
+    //the signer needs its own g1 pubkey to build the proof instance
+    type SecretKey = (PublicKeyShareG1, SecretKeyShare);
+    type Signature = (SignatureShareG1, ZkProofDLEq);
+    type AggregatePublicKey = PublicKeyShareG2;
+    type AggregateSignature = SignatureShareG1;
+    type MessageHash = G1Affine;
+
+    fn name(&self) -> &'static str {
+        "bls sign share in g1 (with nizk)"
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn keygen(&mut self, nodes: usize) -> Vec<(Self::PublicKey, Self::SecretKey)> {
+        let (sk_set, sk_shares) = deal_shares(self.threshold, nodes);
+        // Get the corresponding public key set
+        let pk_set_g1 = sk_set.public_keys();
+        let pk_set_g2 = sk_set.public_keys_g2();
+        sk_shares
+            .into_iter()
+            .enumerate()
+            .map(|(node, sk_share)| {
+                let pk_share_g1 = pk_set_g1.public_key_share(node);
+                let pk_share_g2 = pk_set_g2.public_key_share(node);
+                ((pk_share_g1, pk_share_g2), (pk_share_g1, sk_share))
+            })
+            .collect()
+    }
 
-    //for all sign share in g1 with nizk
-    let mut time_to_verify_all_independent_signs_g1 = 0.0;
+    fn sign(&self, sk: &Self::SecretKey, msg: &[u8]) -> Self::Signature {
+        self.sign_on(sk, &hash_g1(msg))
+    }
 
-    let start_time = Instant::now();
-    for i in 0..nodes {
-        let sig = &signs_g1[i];
-        let key = keypairs[i].0;
-        let g = G1Affine::generator();
-        let g_x = key.0 .0;
-        let h_x = sig.0 .0;
-        let nizk = &nizks[i];
-        let h = hash_g1(msg);
-        let instance = DLEqInstance { g, h, g_x, h_x };
-        verify_proof(&instance, &nizk).unwrap();
-    }
-    let end_time = Instant::now();
-
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs_g1 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for creating aggregated sign g1
-    let mut time_to_create_aggregated_sign_g1 = 0.0;
-
-    let start_time = Instant::now();
-    let mut agg_sign_g1 = signs_g1[0].clone();
-    for i in 1..signs_g1.len() {
-        let new_agg_sign_g1 = aggregate_sign_g1(&agg_sign_g1, &signs_g1[i]);
-        agg_sign_g1 = new_agg_sign_g1;
-    }
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_aggregated_sign_g1 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for creating aggregated pubkey g2
-    let mut time_to_create_aggregated_pubkey_g2 = 0.0;
-
-    let start_time = Instant::now();
-    let mut agg_pubkey_g2 = keypairs[0].1.clone();
-    for i in 1..keypairs.len() {
-        let new_agg_pubkey_g2 = aggregate_pubkey_g2(&agg_pubkey_g2, &keypairs[i].1);
-        agg_pubkey_g2 = new_agg_pubkey_g2;
-    }
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_aggregated_pubkey_g2 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying aggregated sign g1
-    let mut time_to_verify_aggregated_sign_g1 = 0.0;
-
-    let start_time = Instant::now();
-    agg_pubkey_g2.verify(&agg_sign_g1, msg);
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_aggregated_sign_g1 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    let mean_single_sign_creation_g1 = calculate_mean(&time_to_create_sign_share_g1) * 1000.0;
-    let median_single_sign_creation_g1 =
-        calculate_median(&mut time_to_create_sign_share_g1) * 1000.0;
-    let mean_single_sign_verify_g1 = calculate_mean(&time_to_verify_single_sign_share_g1) * 1000.0;
-    let median_single_sign_verify_g1 =
-        calculate_median(&mut time_to_verify_single_sign_share_g1) * 1000.0;
-    let n_sign_verify_g1 = time_to_verify_all_independent_signs_g1 * 1000.0;
-    let creation_agg_pubkey_g2 = time_to_create_aggregated_pubkey_g2 * 1000.0;
-    let creation_agg_sign_g1 = time_to_create_aggregated_sign_g1 * 1000.0;
-    let verify_agg_sign_g1 = time_to_verify_aggregated_sign_g1 * 1000.0;
-
-    //OUTPUT
-    println!("for signshare in g1 : ");
-    println!(
-        "mean time to create single  bls sign share in g1 (with nizk): {:.4} ms",
-        mean_single_sign_creation_g1
-    );
-    println!(
-        "median time to create single bls sign share in g1 (with nizk): {:.4} ms",
-        median_single_sign_creation_g1
-    );
-    println!(
-        "mean time to verify single bls sign share in g1 (with nizk): {:.4} ms",
-        mean_single_sign_verify_g1
-    );
-    println!(
-        "meadian time to verify single bls sign share in g1 (with nizk): {:.4} ms",
-        median_single_sign_verify_g1
-    );
-    println!(
-        "time takes to verify n independent signs share in g1 (with nizk): {:.4} ms",
-        n_sign_verify_g1
-    );
-    println!(
-        "time takes to create aggregated pubkey in g2: {:.4} ms",
-        creation_agg_pubkey_g2
-    );
-    println!(
-        "time takes to create aggregated sign in g1: {:.4} ms",
-        creation_agg_sign_g1
-    );
-    println!(
-        "time takes to verify aggregated sign in g1: {:.4} ms",
-        verify_agg_sign_g1
-    );
-    println!("");
-
-    (
-        mean_single_sign_creation_g1,
-        mean_single_sign_verify_g1,
-        n_sign_verify_g1,
-        creation_agg_pubkey_g2,
-        creation_agg_sign_g1,
-        verify_agg_sign_g1,
-    )
+    fn verify(&self, pk: &Self::PublicKey, sig: &Self::Signature, msg: &[u8]) -> bool {
+        self.verify_on(pk, sig, &hash_g1(msg))
+    }
+
+    //the hash is both signed and the base of the nizk instance
+    fn hash_message(&self, msg: &[u8]) -> Option<G1Affine> {
+        Some(hash_g1(msg))
+    }
+
+    fn sign_hashed(&self, sk: &Self::SecretKey, hash: &G1Affine) -> Option<Self::Signature> {
+        Some(self.sign_on(sk, hash))
+    }
+
+    fn verify_hashed(
+        &self,
+        pk: &Self::PublicKey,
+        sig: &Self::Signature,
+        hash: &G1Affine,
+    ) -> Option<bool> {
+        Some(self.verify_on(pk, sig, hash))
+    }
+
+    //the proof is kept, so it no longer matches the signature
+    fn tampered_signature(&self, sig: &Self::Signature) -> Option<Self::Signature> {
+        let (sig, nizk) = sig;
+        let tampered = SignatureShareG1(SignatureG1(tampered_g1(&sig.0 .0)));
+        Some((tampered, nizk.clone()))
+    }
+
+    fn identity_signature(&self, sig: &Self::Signature) -> Option<Self::Signature> {
+        let (_, nizk) = sig;
+        let identity = SignatureShareG1(SignatureG1(G1Affine::identity()));
+        Some((identity, nizk.clone()))
+    }
+
+    //a proof made with a random scalar instead of the signer's secret key
+    fn forged_proof_signature(
+        &self,
+        sk: &Self::SecretKey,
+        sig: &Self::Signature,
+        msg: &[u8],
+    ) -> Option<Self::Signature> {
+        let (pk, _) = sk;
+        let (sig, _) = sig;
+        let instance = DLEqInstance {
+            g: G1Affine::generator(),
+            h: hash_g1(msg),
+            g_x: pk.0 .0,
+            h_x: sig.0 .0,
+        };
+        let witness = with_rng(|rng| DLEqWitness {
+            scalar_x: Fr::random(&mut *rng),
+            scalar_r: Fr::random(&mut *rng),
+        });
+        Some((sig.clone(), prove_gen(&instance, &witness)))
+    }
+
+    fn aggregate_public_keys(&self, pks: &[Self::PublicKey]) -> Option<PublicKeyShareG2> {
+        Some(aggregate_pubkey_g2(
+            pks,
+            |(_, pk)| pk,
+            Reduction::Sequential,
+        ))
+    }
+
+    fn aggregate_public_keys_parallel(&self, pks: &[Self::PublicKey]) -> Option<PublicKeyShareG2> {
+        Some(aggregate_pubkey_g2(pks, |(_, pk)| pk, Reduction::Parallel))
+    }
+
+    //the proofs are only checked on the shares, not aggregated
+    fn aggregate_signatures(&self, sigs: &[(usize, Self::Signature)]) -> Option<SignatureShareG1> {
+        Some(aggregate_sign_g1(
+            sigs,
+            |(_, (sig, _))| sig,
+            Reduction::Sequential,
+        ))
+    }
+
+    fn aggregate_signatures_parallel(
+        &self,
+        sigs: &[(usize, Self::Signature)],
+    ) -> Option<SignatureShareG1> {
+        Some(aggregate_sign_g1(
+            sigs,
+            |(_, (sig, _))| sig,
+            Reduction::Parallel,
+        ))
+    }
+
+    fn verify_aggregate(
+        &self,
+        agg_pk: Option<&PublicKeyShareG2>,
+        agg_sig: &SignatureShareG1,
+        msg: &[u8],
+    ) -> bool {
+        agg_pk.is_some_and(|agg_pk| agg_pk.verify(agg_sig, msg))
+    }
 }
 
-pub fn aggregate_sign_g1(
-    agg_sig: &SignatureShareG1,
-    new_sign: &SignatureShareG1,
-) -> SignatureShareG1 {
-    let agg_sign = G1Affine::from(agg_sig.0 .0 + G1Projective::from(new_sign.0 .0));
-    let sign = SignatureShareG1(SignatureG1(agg_sign));
-    sign
+//nizk proofs are only produced for signatures in g1
+#[derive(Clone, Debug, Serialize)]
+pub struct NizkMultisigBlsReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub g1: SchemeReport,
+}
+
+impl CsvReport for NizkMultisigBlsReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            ("hash_to_curve_g1", CsvCell::Timing(self.g1.hash)),
+            (
+                "creation_single_sign_share_g1",
+                CsvCell::Timing(Some(self.g1.sign)),
+            ),
+            (
+                "verify_single_sign_share_g1",
+                CsvCell::Timing(Some(self.g1.verify)),
+            ),
+            (
+                "verify_n_signshares_g1",
+                CsvCell::Timing(Some(self.g1.verify_n)),
+            ),
+            (
+                "verify_n_signshares_parallel_g1",
+                CsvCell::Parallel(self.g1.verify_n_parallel.clone()),
+            ),
+            (
+                "creation_agg_pubkey_g2",
+                CsvCell::Timing(self.g1.aggregate_pubkey),
+            ),
+            (
+                "creation_agg_pubkey_parallel_g2",
+                CsvCell::Timing(self.g1.aggregate_pubkey_parallel),
+            ),
+            (
+                "creation_agg_sign_g1",
+                CsvCell::Timing(self.g1.aggregate_sign),
+            ),
+            (
+                "creation_agg_sign_parallel_g1",
+                CsvCell::Timing(self.g1.aggregate_sign_parallel),
+            ),
+            (
+                "verify_agg_sig_g1",
+                CsvCell::Timing(self.g1.verify_aggregate),
+            ),
+            (
+                "reject_wrong_msg_g1",
+                CsvCell::Timing(Some(self.g1.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey_g1",
+                CsvCell::Timing(self.g1.reject_wrong_pubkey),
+            ),
+            (
+                "reject_tampered_sig_g1",
+                CsvCell::Timing(self.g1.reject_tampered_sign),
+            ),
+            (
+                "reject_identity_sig_g1",
+                CsvCell::Timing(self.g1.reject_identity_sign),
+            ),
+            (
+                "reject_forged_proof_g1",
+                CsvCell::Timing(self.g1.reject_forged_proof),
+            ),
+            ("throughput_g1", CsvCell::Throughput(self.g1.throughput)),
+            ("verify_failures", CsvCell::value(self.g1.verify_failures)),
+        ]);
+        cells
+    }
+
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        vec![&self.g1]
+    }
 }
 
-pub fn aggregate_pubkey_g2(
-    agg_key: &PublicKeyShareG2,
-    new_key: &PublicKeyShareG2,
-) -> PublicKeyShareG2 {
-    let agg_key = G2Affine::from(agg_key.0 .0 + G2Projective::from(new_key.0 .0));
-    let key = PublicKeyShareG2(PublicKeyG2(agg_key));
-    key
+pub fn benchmark_nizk_multisig_bls(
+    nodes: usize,
+    threshold: usize,
+    params: &BenchParams,
+) -> Result<NizkMultisigBlsReport, BenchError> {
+    let g1 = benchmark_scheme(&mut NizkMultisigBlsG1 { threshold }, nodes, params)?;
+
+    Ok(NizkMultisigBlsReport {
+        run: RunInfo::new(nodes, params),
+        g1,
+    })
 }
@@ -0,0 +1,440 @@
+use super::forgery::{tampered_g1, tampered_g2};
+use super::multisig_bls::{aggregation_cells, sum_points, Reduction};
+use super::report::{group_outcome_cells, CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::{benchmark_scheme, BenchError, BenchParams, Group, SignatureScheme};
+use blsttc::group::prime::PrimeCurveAffine;
+use blsttc::group::Curve;
+use blsttc::rand::Rng;
+use blsttc::{
+    hash_g1, hash_g2, G1Affine, G2Affine, PublicKeyG1, PublicKeyG2, SecretKey, SignatureG1,
+    SignatureG2,
+};
+use serde::Serialize;
+
+//prefix of the message a proof of possession signs; the ietf draft hashes
+//proofs with a dst of their own, but blsttc only hashes with the one of
+//messages, so proofs are separated by the message instead
+const POP_TAG: &[u8] = b"BLS_POP_";
+
+fn pop_message(key: &[u8]) -> Vec<u8> {
+    [POP_TAG, key].concat()
+}
+
+//pubkey in g2 as published by its node, with the proof that the node knows
+//its secret key; only keys whose proof was checked are aggregated
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProvenKeyG2 {
+    pub key: PublicKeyG2,
+    pub pop: Option<SignatureG1>,
+    //only set by `verify_possession`
+    proven: bool,
+}
+
+impl ProvenKeyG2 {
+    fn unproven(key: PublicKeyG2) -> Self {
+        ProvenKeyG2 {
+            key,
+            pop: None,
+            proven: false,
+        }
+    }
+
+    pub fn is_proven(&self) -> bool {
+        self.proven
+    }
+
+    fn pop_hash(&self) -> G1Affine {
+        hash_g1(pop_message(&self.key.to_bytes()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProvenKeyG1 {
+    pub key: PublicKeyG1,
+    pub pop: Option<SignatureG2>,
+    //only set by `verify_possession`
+    proven: bool,
+}
+
+impl ProvenKeyG1 {
+    fn unproven(key: PublicKeyG1) -> Self {
+        ProvenKeyG1 {
+            key,
+            pop: None,
+            proven: false,
+        }
+    }
+
+    pub fn is_proven(&self) -> bool {
+        self.proven
+    }
+
+    fn pop_hash(&self) -> G2Affine {
+        hash_g2(pop_message(&self.key.to_bytes()))
+    }
+}
+
+//multisig signs in g1 from independent keys, aggregated and verified
+//against the aggregated pubkey in g2; keys come with a proof of
+//possession so a rogue key can't cancel out the keys of others
+pub struct MultisigBlsPopG1;
+
+impl SignatureScheme for MultisigBlsPopG1 {
+    type PublicKey = ProvenKeyG2;
+    type SecretKey = SecretKey;
+    type Signature = SignatureG1;
+    type AggregatePublicKey = PublicKeyG2;
+    type AggregateSignature = SignatureG1;
+    type MessageHash = G1Affine;
+
+    fn name(&self) -> &'static str {
+        "bls multisig sign with pop in g1"
+    }
+
+    fn keygen(&mut self, nodes: usize) -> Vec<(ProvenKeyG2, SecretKey)> {
+        (0..nodes)
+            .map(|_| {
+                let sk: SecretKey = with_rng(|rng| rng.gen());
+                (ProvenKeyG2::unproven(sk.public_key_g2()), sk)
+            })
+            .collect()
+    }
+
+    //the proof is a sign in the group of the signs, of the pubkey itself
+    fn prove_possession(&self, pk: &mut ProvenKeyG2, sk: &SecretKey) -> bool {
+        pk.pop = Some(sk.sign_g1(pk.pop_hash()));
+        true
+    }
+
+    fn verify_possession(&self, pk: &mut ProvenKeyG2) -> bool {
+        pk.proven = pk
+            .pop
+            .as_ref()
+            .is_some_and(|pop| pk.key.verify_g1(pop, pk.pop_hash()));
+        pk.proven
+    }
+
+    //g2^x minus the sum of `pks`, so that the aggregate of all of them is
+    //g2^x; its proof can only be made with x
+    fn rogue_public_key(&self, pks: &[ProvenKeyG2]) -> Option<ProvenKeyG2> {
+        let x: SecretKey = with_rng(|rng| rng.gen());
+        let sum = sum_points(pks, |pk| pk.key.0, Reduction::Sequential);
+        let rogue = x.public_key_g2().0.to_curve() - sum.to_curve();
+        let mut rogue = ProvenKeyG2::unproven(PublicKeyG2(rogue.to_affine()));
+        self.prove_possession(&mut rogue, &x);
+        Some(rogue)
+    }
+
+    fn sign(&self, sk: &SecretKey, msg: &[u8]) -> SignatureG1 {
+        sk.sign_g1(hash_g1(msg))
+    }
+
+    fn verify(&self, pk: &ProvenKeyG2, sig: &SignatureG1, msg: &[u8]) -> bool {
+        pk.key.verify(sig, msg)
+    }
+
+    fn hash_message(&self, msg: &[u8]) -> Option<G1Affine> {
+        Some(hash_g1(msg))
+    }
+
+    fn sign_hashed(&self, sk: &SecretKey, hash: &G1Affine) -> Option<SignatureG1> {
+        Some(sk.sign_g1(*hash))
+    }
+
+    fn verify_hashed(&self, pk: &ProvenKeyG2, sig: &SignatureG1, hash: &G1Affine) -> Option<bool> {
+        Some(pk.key.verify_g1(sig, *hash))
+    }
+
+    fn tampered_signature(&self, sig: &SignatureG1) -> Option<SignatureG1> {
+        Some(SignatureG1(tampered_g1(&sig.0)))
+    }
+
+    fn identity_signature(&self, _sig: &SignatureG1) -> Option<SignatureG1> {
+        Some(SignatureG1(G1Affine::identity()))
+    }
+
+    fn aggregate_public_keys(&self, pks: &[ProvenKeyG2]) -> Option<PublicKeyG2> {
+        aggregate_pubkey_g2(pks, Reduction::Sequential)
+    }
+
+    fn aggregate_public_keys_parallel(&self, pks: &[ProvenKeyG2]) -> Option<PublicKeyG2> {
+        aggregate_pubkey_g2(pks, Reduction::Parallel)
+    }
+
+    fn aggregate_signatures(&self, sigs: &[(usize, SignatureG1)]) -> Option<SignatureG1> {
+        let agg_sign = sum_points(sigs, |(_, sig)| sig.0, Reduction::Sequential);
+        Some(SignatureG1(agg_sign))
+    }
+
+    fn aggregate_signatures_parallel(&self, sigs: &[(usize, SignatureG1)]) -> Option<SignatureG1> {
+        let agg_sign = sum_points(sigs, |(_, sig)| sig.0, Reduction::Parallel);
+        Some(SignatureG1(agg_sign))
+    }
+
+    fn verify_aggregate(
+        &self,
+        agg_pk: Option<&PublicKeyG2>,
+        agg_sig: &SignatureG1,
+        msg: &[u8],
+    ) -> bool {
+        agg_pk.is_some_and(|agg_pk| agg_pk.verify(agg_sig, msg))
+    }
+}
+
+//multisig signs in g2 from independent keys, aggregated and verified
+//against the aggregated pubkey in g1
+pub struct MultisigBlsPopG2;
+
+impl SignatureScheme for MultisigBlsPopG2 {
+    type PublicKey = ProvenKeyG1;
+    type SecretKey = SecretKey;
+    type Signature = SignatureG2;
+    type AggregatePublicKey = PublicKeyG1;
+    type AggregateSignature = SignatureG2;
+    type MessageHash = G2Affine;
+
+    fn name(&self) -> &'static str {
+        "bls multisig sign with pop in g2"
+    }
+
+    fn keygen(&mut self, nodes: usize) -> Vec<(ProvenKeyG1, SecretKey)> {
+        (0..nodes)
+            .map(|_| {
+                let sk: SecretKey = with_rng(|rng| rng.gen());
+                (ProvenKeyG1::unproven(sk.public_key_g1()), sk)
+            })
+            .collect()
+    }
+
+    fn prove_possession(&self, pk: &mut ProvenKeyG1, sk: &SecretKey) -> bool {
+        pk.pop = Some(sk.sign_g2(pk.pop_hash()));
+        true
+    }
+
+    fn verify_possession(&self, pk: &mut ProvenKeyG1) -> bool {
+        pk.proven = pk
+            .pop
+            .as_ref()
+            .is_some_and(|pop| pk.key.verify_g2(pop, pk.pop_hash()));
+        pk.proven
+    }
+
+    fn rogue_public_key(&self, pks: &[ProvenKeyG1]) -> Option<ProvenKeyG1> {
+        let x: SecretKey = with_rng(|rng| rng.gen());
+        let sum = sum_points(pks, |pk| pk.key.0, Reduction::Sequential);
+        let rogue = x.public_key_g1().0.to_curve() - sum.to_curve();
+        let mut rogue = ProvenKeyG1::unproven(PublicKeyG1(rogue.to_affine()));
+        self.prove_possession(&mut rogue, &x);
+        Some(rogue)
+    }
+
+    fn sign(&self, sk: &SecretKey, msg: &[u8]) -> SignatureG2 {
+        sk.sign_g2(hash_g2(msg))
+    }
+
+    fn verify(&self, pk: &ProvenKeyG1, sig: &SignatureG2, msg: &[u8]) -> bool {
+        pk.key.verify(sig, msg)
+    }
+
+    fn hash_message(&self, msg: &[u8]) -> Option<G2Affine> {
+        Some(hash_g2(msg))
+    }
+
+    fn sign_hashed(&self, sk: &SecretKey, hash: &G2Affine) -> Option<SignatureG2> {
+        Some(sk.sign_g2(*hash))
+    }
+
+    fn verify_hashed(&self, pk: &ProvenKeyG1, sig: &SignatureG2, hash: &G2Affine) -> Option<bool> {
+        Some(pk.key.verify_g2(sig, *hash))
+    }
+
+    fn tampered_signature(&self, sig: &SignatureG2) -> Option<SignatureG2> {
+        Some(SignatureG2(tampered_g2(&sig.0)))
+    }
+
+    fn identity_signature(&self, _sig: &SignatureG2) -> Option<SignatureG2> {
+        Some(SignatureG2(G2Affine::identity()))
+    }
+
+    fn aggregate_public_keys(&self, pks: &[ProvenKeyG1]) -> Option<PublicKeyG1> {
+        aggregate_pubkey_g1(pks, Reduction::Sequential)
+    }
+
+    fn aggregate_public_keys_parallel(&self, pks: &[ProvenKeyG1]) -> Option<PublicKeyG1> {
+        aggregate_pubkey_g1(pks, Reduction::Parallel)
+    }
+
+    fn aggregate_signatures(&self, sigs: &[(usize, SignatureG2)]) -> Option<SignatureG2> {
+        let agg_sign = sum_points(sigs, |(_, sig)| sig.0, Reduction::Sequential);
+        Some(SignatureG2(agg_sign))
+    }
+
+    fn aggregate_signatures_parallel(&self, sigs: &[(usize, SignatureG2)]) -> Option<SignatureG2> {
+        let agg_sign = sum_points(sigs, |(_, sig)| sig.0, Reduction::Parallel);
+        Some(SignatureG2(agg_sign))
+    }
+
+    fn verify_aggregate(
+        &self,
+        agg_pk: Option<&PublicKeyG1>,
+        agg_sig: &SignatureG2,
+        msg: &[u8],
+    ) -> bool {
+        agg_pk.is_some_and(|agg_pk| agg_pk.verify(agg_sig, msg))
+    }
+}
+
+//aggregated pubkey of `pks`, or none if any of them wasn't proven
+fn aggregate_pubkey_g1(pks: &[ProvenKeyG1], reduction: Reduction) -> Option<PublicKeyG1> {
+    pks.iter()
+        .all(|pk| pk.proven)
+        .then(|| PublicKeyG1(sum_points(pks, |pk| pk.key.0, reduction)))
+}
+
+fn aggregate_pubkey_g2(pks: &[ProvenKeyG2], reduction: Reduction) -> Option<PublicKeyG2> {
+    pks.iter()
+        .all(|pk| pk.proven)
+        .then(|| PublicKeyG2(sum_points(pks, |pk| pk.key.0, reduction)))
+}
+
+//reports are keyed by the group of the signatures; the aggregated pubkey
+//and the proven keys live in the opposite group
+#[derive(Clone, Debug, Serialize)]
+pub struct MultisigBlsPopReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub g1: Option<SchemeReport>,
+    pub g2: Option<SchemeReport>,
+}
+
+impl CsvReport for MultisigBlsPopReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            (
+                "prove_possession_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.prove_possession)),
+            ),
+            (
+                "prove_possession_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.prove_possession)),
+            ),
+            (
+                "verify_possession_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.verify_possession)),
+            ),
+            (
+                "verify_possession_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.verify_possession)),
+            ),
+            //keygen and both proof timings, in milliseconds
+            (
+                "setup_total_g1",
+                CsvCell::Value(
+                    self.g1
+                        .as_ref()
+                        .map(|g1| format!("{:.4}", g1.setup()))
+                        .unwrap_or_default(),
+                ),
+            ),
+            (
+                "setup_total_g2",
+                CsvCell::Value(
+                    self.g2
+                        .as_ref()
+                        .map(|g2| format!("{:.4}", g2.setup()))
+                        .unwrap_or_default(),
+                ),
+            ),
+            (
+                "hash_to_curve_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.hash)),
+            ),
+            (
+                "hash_to_curve_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.hash)),
+            ),
+            (
+                "creation_single_sign_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.sign)),
+            ),
+            (
+                "creation_single_sign_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.sign)),
+            ),
+            (
+                "verify_single_sign_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify)),
+            ),
+            (
+                "verify_single_sign_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify)),
+            ),
+            (
+                "verify_n_signs_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify_n)),
+            ),
+            (
+                "verify_n_signs_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify_n)),
+            ),
+            (
+                "verify_n_signs_parallel_g1",
+                CsvCell::Parallel(
+                    self.g1
+                        .iter()
+                        .flat_map(|g1| g1.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+            (
+                "verify_n_signs_parallel_g2",
+                CsvCell::Parallel(
+                    self.g2
+                        .iter()
+                        .flat_map(|g2| g2.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+        ]);
+        cells.extend(aggregation_cells(&self.g1, &self.g2));
+        cells.extend([
+            (
+                "reject_rogue_key_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.reject_rogue_key)),
+            ),
+            (
+                "reject_rogue_key_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.reject_rogue_key)),
+            ),
+        ]);
+        cells.extend(group_outcome_cells(&self.g1, &self.g2));
+        cells
+    }
+
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        [&self.g1, &self.g2].into_iter().flatten().collect()
+    }
+}
+
+pub fn benchmark_pop_multisig_bls(
+    nodes: usize,
+    groups: &[Group],
+    params: &BenchParams,
+) -> Result<MultisigBlsPopReport, BenchError> {
+    let g1 = groups
+        .contains(&Group::G1)
+        .then(|| benchmark_scheme(&mut MultisigBlsPopG1, nodes, params))
+        .transpose()?;
+    let g2 = groups
+        .contains(&Group::G2)
+        .then(|| benchmark_scheme(&mut MultisigBlsPopG2, nodes, params))
+        .transpose()?;
+
+    Ok(MultisigBlsPopReport {
+        run: RunInfo::new(nodes, params),
+        g1,
+        g2,
+    })
+}
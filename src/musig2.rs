@@ -0,0 +1,520 @@
+use super::multisig_bls::Reduction;
+use super::report::{CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::schnorr::tagged_hash;
+use super::{benchmark_scheme, BenchError, BenchParams, SignatureScheme};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::{LinearCombination, Reduce};
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::schnorr;
+use k256::schnorr::signature::Verifier as _;
+use k256::{AffinePoint, NonZeroScalar, ProjectivePoint, PublicKey, Scalar, U256};
+use rayon::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+//musig2 (bip-327) over secp256k1: nodes commit to two nonces each in a
+//first round, then sign in a second round; the partial signs sum to a
+//plain bip-340 sign of the sha-256 digest of the message under the
+//aggregated key
+//
+//a run is one signing session, the nonces are drawn once per run and every
+//node signs the same message with them, so no nonce is ever used for two
+//different challenges
+#[derive(Default)]
+pub struct MuSig2 {
+    //set by keygen
+    pks: Vec<PublicKey>,
+    //aggregated in the nonce round, which sets up the session, so that no
+    //sign pays for it; the aggregation is also timed on its own as the
+    //aggregate pubkey, so keygen leaves it out
+    key_agg: Option<KeyAgg>,
+    //set by the nonce round
+    nonces: Option<Nonces>,
+}
+
+//secret key of a node, with the index its nonces are kept under
+pub struct SignerKey {
+    index: usize,
+    sk: NonZeroScalar,
+    pk: PublicKey,
+}
+
+//partial sign of a node; it carries the final nonce of the session so the
+//signs can be aggregated without the message
+#[derive(Clone, Copy, Debug)]
+pub struct PartialSignature {
+    signer: usize,
+    nonce: AffinePoint,
+    s: Scalar,
+}
+
+//aggregated key of the committee, with what signing needs to weigh each key
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyAgg {
+    //hash of the list of all pubkeys, which every coefficient commits to
+    list_hash: [u8; 32],
+    //first key that differs from the first one, which gets coefficient 1
+    second_key: Option<PublicKey>,
+    key: AffinePoint,
+}
+
+impl KeyAgg {
+    fn new(pks: &[PublicKey], reduction: Reduction) -> Self {
+        let list_hash = pks
+            .iter()
+            .fold(tagged_hash(b"KeyAgg list"), |hash, pk| {
+                hash.chain_update(pk.as_affine().to_bytes())
+            })
+            .finalize()
+            .into();
+        let second_key = pks.iter().find(|pk| Some(*pk) != pks.first()).copied();
+        let mut key_agg = KeyAgg {
+            list_hash,
+            second_key,
+            key: AffinePoint::IDENTITY,
+        };
+
+        let weighted = |pk: &PublicKey| pk.to_projective() * key_agg.coefficient(pk);
+        let key = match reduction {
+            Reduction::Sequential => pks
+                .iter()
+                .map(weighted)
+                .fold(ProjectivePoint::IDENTITY, |sum, point| sum + point),
+            Reduction::Parallel => pks
+                .par_iter()
+                .map(weighted)
+                .reduce(|| ProjectivePoint::IDENTITY, |left, right| left + right),
+        };
+        key_agg.key = key.to_affine();
+        key_agg
+    }
+
+    fn coefficient(&self, pk: &PublicKey) -> Scalar {
+        if Some(pk) == self.second_key.as_ref() {
+            return Scalar::ONE;
+        }
+        let hash = tagged_hash(b"KeyAgg coefficient")
+            .chain_update(self.list_hash)
+            .chain_update(pk.as_affine().to_bytes())
+            .finalize();
+        <Scalar as Reduce<U256>>::reduce_bytes(&hash)
+    }
+
+    //bip-340 keys are x-only, so secret keys are negated when the
+    //aggregated key has odd y
+    fn parity(&self) -> Scalar {
+        if self.key.y_is_odd().into() {
+            -Scalar::ONE
+        } else {
+            Scalar::ONE
+        }
+    }
+}
+
+//nonces of the first round, kept by index of the node
+struct Nonces {
+    secret: Vec<[Scalar; 2]>,
+    public: Vec<[AffinePoint; 2]>,
+    //sum of the public nonces of all nodes
+    aggregate: [AffinePoint; 2],
+}
+
+//values every node derives from the aggregated nonce and the message
+struct Session {
+    //weight of the second nonce
+    b: Scalar,
+    //final nonce, the r of the aggregated sign
+    nonce: AffinePoint,
+    //bip-340 challenge
+    e: Scalar,
+}
+
+impl MuSig2 {
+    fn key_agg(&self) -> &KeyAgg {
+        self.key_agg
+            .as_ref()
+            .expect("keys are aggregated before signing")
+    }
+
+    fn nonces(&self) -> &Nonces {
+        self.nonces
+            .as_ref()
+            .expect("nonces are committed before signing")
+    }
+
+    fn session(&self, msg: &[u8]) -> Session {
+        let key = self.key_agg().key;
+        let [r1, r2] = self.nonces().aggregate;
+        let digest = Sha256::digest(msg);
+
+        let b = <Scalar as Reduce<U256>>::reduce_bytes(
+            &tagged_hash(b"MuSig/noncecoef")
+                .chain_update(r1.to_bytes())
+                .chain_update(r2.to_bytes())
+                .chain_update(key.x())
+                .chain_update(digest)
+                .finalize(),
+        );
+        let nonce = ProjectivePoint::from(r1) + r2 * b;
+        //only happens if the nodes collude on their nonces
+        let nonce = if nonce == ProjectivePoint::IDENTITY {
+            AffinePoint::GENERATOR
+        } else {
+            nonce.to_affine()
+        };
+        let e = <Scalar as Reduce<U256>>::reduce_bytes(
+            &tagged_hash(b"BIP0340/challenge")
+                .chain_update(nonce.x())
+                .chain_update(key.x())
+                .chain_update(digest)
+                .finalize(),
+        );
+
+        Session { b, nonce, e }
+    }
+}
+
+impl SignatureScheme for MuSig2 {
+    type PublicKey = PublicKey;
+    type SecretKey = SignerKey;
+    type Signature = PartialSignature;
+    type AggregatePublicKey = KeyAgg;
+    type AggregateSignature = schnorr::Signature;
+    type MessageHash = ();
+
+    fn name(&self) -> &'static str {
+        "musig2 partial sign"
+    }
+
+    fn keygen(&mut self, nodes: usize) -> Vec<(PublicKey, SignerKey)> {
+        let keypairs: Vec<_> = (0..nodes)
+            .map(|index| {
+                let sk = with_rng(NonZeroScalar::random);
+                let pk = PublicKey::from_secret_scalar(&sk);
+                (pk, SignerKey { index, sk, pk })
+            })
+            .collect();
+
+        self.pks = keypairs.iter().map(|(pk, _)| *pk).collect();
+        self.key_agg = None;
+        self.nonces = None;
+        keypairs
+    }
+
+    fn commit_nonces(&mut self) -> bool {
+        self.key_agg = Some(KeyAgg::new(&self.pks, Reduction::Sequential));
+        let secret: Vec<[Scalar; 2]> = (0..self.pks.len())
+            .map(|_| with_rng(|rng| [0; 2].map(|_| *NonZeroScalar::random(&mut *rng))))
+            .collect();
+        let public: Vec<[AffinePoint; 2]> = secret
+            .iter()
+            .map(|nonces| nonces.map(|k| (ProjectivePoint::GENERATOR * k).to_affine()))
+            .collect();
+        let aggregate = [0, 1].map(|j| {
+            public
+                .iter()
+                .fold(ProjectivePoint::IDENTITY, |sum, nonces| sum + nonces[j])
+                .to_affine()
+        });
+
+        self.nonces = Some(Nonces {
+            secret,
+            public,
+            aggregate,
+        });
+        true
+    }
+
+    fn sign(&self, sk: &SignerKey, msg: &[u8]) -> PartialSignature {
+        let key_agg = self.key_agg();
+        let session = self.session(msg);
+        let [k1, k2] = self.nonces().secret[sk.index];
+        //negated for the same reason as the key, the final nonce is x-only
+        let k = k1 + session.b * k2;
+        let k = if session.nonce.y_is_odd().into() {
+            -k
+        } else {
+            k
+        };
+        let d = key_agg.parity() * *sk.sk;
+
+        PartialSignature {
+            signer: sk.index,
+            nonce: session.nonce,
+            s: k + session.e * key_agg.coefficient(&sk.pk) * d,
+        }
+    }
+
+    //s * G must be the effective nonce of the signer plus its weighted
+    //share of the challenge
+    fn verify(&self, pk: &PublicKey, sig: &PartialSignature, msg: &[u8]) -> bool {
+        let key_agg = self.key_agg();
+        let Some([r1, r2]) = self.nonces().public.get(sig.signer) else {
+            return false;
+        };
+        let session = self.session(msg);
+        let nonce = ProjectivePoint::from(*r1) + *r2 * session.b;
+        let nonce = if session.nonce.y_is_odd().into() {
+            -nonce
+        } else {
+            nonce
+        };
+        let challenge = session.e * key_agg.coefficient(pk) * key_agg.parity();
+
+        ProjectivePoint::lincomb(
+            &ProjectivePoint::GENERATOR,
+            &sig.s,
+            &pk.to_projective(),
+            &-challenge,
+        ) == nonce
+    }
+
+    //s + 1
+    fn tampered_signature(&self, sig: &PartialSignature) -> Option<PartialSignature> {
+        Some(PartialSignature {
+            s: sig.s + Scalar::ONE,
+            ..*sig
+        })
+    }
+
+    fn aggregate_public_keys(&self, pks: &[PublicKey]) -> Option<KeyAgg> {
+        Some(KeyAgg::new(pks, Reduction::Sequential))
+    }
+
+    //the list hash is sequential either way, only the weighted keys are
+    //summed in parallel
+    fn aggregate_public_keys_parallel(&self, pks: &[PublicKey]) -> Option<KeyAgg> {
+        Some(KeyAgg::new(pks, Reduction::Parallel))
+    }
+
+    fn aggregate_signatures(
+        &self,
+        sigs: &[(usize, PartialSignature)],
+    ) -> Option<schnorr::Signature> {
+        let (_, first) = sigs.first()?;
+        let s = sigs.iter().fold(Scalar::ZERO, |sum, (_, sig)| sum + sig.s);
+        final_signature(first.nonce, s)
+    }
+
+    fn aggregate_signatures_parallel(
+        &self,
+        sigs: &[(usize, PartialSignature)],
+    ) -> Option<schnorr::Signature> {
+        let (_, first) = sigs.first()?;
+        let s = sigs
+            .par_iter()
+            .map(|(_, sig)| sig.s)
+            .reduce(|| Scalar::ZERO, |left, right| left + right);
+        final_signature(first.nonce, s)
+    }
+
+    //the aggregated sign is checked as any bip-340 sign, against the x-only
+    //aggregated key
+    fn verify_aggregate(
+        &self,
+        agg_pk: Option<&KeyAgg>,
+        agg_sig: &schnorr::Signature,
+        msg: &[u8],
+    ) -> bool {
+        agg_pk
+            .and_then(|agg_pk| schnorr::VerifyingKey::from_bytes(&agg_pk.key.x()).ok())
+            .is_some_and(|agg_pk| agg_pk.verify(msg, agg_sig).is_ok())
+    }
+}
+
+//bip-340 sign made of the final nonce and the sum of the partial signs
+fn final_signature(nonce: AffinePoint, s: Scalar) -> Option<schnorr::Signature> {
+    let mut bytes = [0u8; schnorr::Signature::BYTE_SIZE];
+    bytes[..32].copy_from_slice(&nonce.x());
+    bytes[32..].copy_from_slice(&s.to_bytes());
+    schnorr::Signature::try_from(&bytes[..]).ok()
+}
+
+//same columns as the bls multisig report, for a single scheme
+#[derive(Clone, Debug, Serialize)]
+pub struct MuSig2Report {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub musig2: SchemeReport,
+}
+
+impl CsvReport for MuSig2Report {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            //key aggregation and the nonces of all nodes
+            (
+                "commit_nonces_round",
+                CsvCell::Timing(self.musig2.commit_nonces),
+            ),
+            (
+                "creation_single_sign_share",
+                CsvCell::Timing(Some(self.musig2.sign)),
+            ),
+            (
+                "verify_single_sign_share",
+                CsvCell::Timing(Some(self.musig2.verify)),
+            ),
+            (
+                "verify_n_signshares",
+                CsvCell::Timing(Some(self.musig2.verify_n)),
+            ),
+            (
+                "verify_n_signshares_parallel",
+                CsvCell::Parallel(self.musig2.verify_n_parallel.clone()),
+            ),
+            (
+                "creation_agg_pubkey",
+                CsvCell::Timing(self.musig2.aggregate_pubkey),
+            ),
+            (
+                "creation_agg_pubkey_parallel",
+                CsvCell::Timing(self.musig2.aggregate_pubkey_parallel),
+            ),
+            (
+                "creation_agg_sign",
+                CsvCell::Timing(self.musig2.aggregate_sign),
+            ),
+            (
+                "creation_agg_sign_parallel",
+                CsvCell::Timing(self.musig2.aggregate_sign_parallel),
+            ),
+            (
+                "verify_agg_sig",
+                CsvCell::Timing(self.musig2.verify_aggregate),
+            ),
+            (
+                "reject_wrong_msg",
+                CsvCell::Timing(Some(self.musig2.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey",
+                CsvCell::Timing(self.musig2.reject_wrong_pubkey),
+            ),
+            (
+                "reject_tampered_sig",
+                CsvCell::Timing(self.musig2.reject_tampered_sign),
+            ),
+            ("throughput", CsvCell::Throughput(self.musig2.throughput)),
+            (
+                "verify_failures",
+                CsvCell::value(self.musig2.verify_failures),
+            ),
+        ]);
+        cells
+    }
+
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        vec![&self.musig2]
+    }
+}
+
+pub fn benchmark_musig2(nodes: usize, params: &BenchParams) -> Result<MuSig2Report, BenchError> {
+    let musig2 = benchmark_scheme(&mut MuSig2::default(), nodes, params)?;
+
+    Ok(MuSig2Report {
+        run: RunInfo::new(nodes, params),
+        musig2,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::reseed;
+
+    const MSG: &[u8] = b"musig2 test message";
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    type Keypairs = Vec<(PublicKey, SignerKey)>;
+
+    //a signing session of `nodes` nodes, up to the partial signs
+    fn partial_signs(nodes: usize) -> (MuSig2, Keypairs, Vec<(usize, PartialSignature)>) {
+        reseed(1, nodes as u64);
+        let mut musig2 = MuSig2::default();
+        let keypairs = musig2.keygen(nodes);
+        assert!(musig2.commit_nonces());
+        let sigs = keypairs
+            .iter()
+            .map(|(_, sk)| (sk.index, musig2.sign(sk, MSG)))
+            .collect();
+        (musig2, keypairs, sigs)
+    }
+
+    #[test]
+    fn aggregate_is_a_bip340_signature() {
+        let (musig2, keypairs, sigs) = partial_signs(5);
+        for ((pk, _), (_, sig)) in keypairs.iter().zip(&sigs) {
+            assert!(musig2.verify(pk, sig, MSG));
+        }
+        let pks: Vec<_> = keypairs.iter().map(|(pk, _)| *pk).collect();
+        let agg_pk = musig2.aggregate_public_keys(&pks).unwrap();
+        //the nodes signed under the key aggregated in the nonce round
+        assert_eq!(musig2.key_agg.as_ref(), Some(&agg_pk));
+        let agg_sig = musig2.aggregate_signatures(&sigs).unwrap();
+
+        let key = schnorr::VerifyingKey::from_bytes(&agg_pk.key.x()).unwrap();
+        assert!(key.verify(MSG, &agg_sig).is_ok());
+        assert!(key.verify(b"another message", &agg_sig).is_err());
+        assert_eq!(musig2.aggregate_signatures_parallel(&sigs), Some(agg_sig));
+    }
+
+    #[test]
+    fn tampered_partial_signature_is_rejected() {
+        let (musig2, keypairs, mut sigs) = partial_signs(3);
+        let tampered = musig2.tampered_signature(&sigs[1].1).unwrap();
+        assert!(!musig2.verify(&keypairs[1].0, &tampered, MSG));
+        //nor does it pass as the share of another node
+        assert!(!musig2.verify(&keypairs[0].0, &sigs[1].1, MSG));
+
+        sigs[1].1 = tampered;
+        let pks: Vec<_> = keypairs.iter().map(|(pk, _)| *pk).collect();
+        let agg_pk = musig2.aggregate_public_keys(&pks);
+        let agg_sig = musig2.aggregate_signatures(&sigs).unwrap();
+        assert!(!musig2.verify_aggregate(agg_pk.as_ref(), &agg_sig, MSG));
+    }
+
+    //key_agg_vectors.json of bip-327
+    #[test]
+    fn key_aggregation_matches_bip327() {
+        let pks: Vec<PublicKey> = [
+            "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "03DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "023590A94E768F8E1815C2F24B4D80A8E3149316C3518CE7B7AD338368D038CA66",
+        ]
+        .map(|pk| PublicKey::from_sec1_bytes(&from_hex(pk)).unwrap())
+        .to_vec();
+        let cases: [(&[usize], &str); 4] = [
+            (
+                &[0, 1, 2],
+                "90539EEDE565F5D054F32CC0C220126889ED1E5D193BAF15AEF344FE59D4610C",
+            ),
+            (
+                &[2, 1, 0],
+                "6204DE8B083426DC6EAF9502D27024D53FC826BF7D2012148A0575435DF54B2B",
+            ),
+            (
+                &[0, 0, 0],
+                "B436E3BAD62B8CD409969A224731C193D051162D8C5AE8B109306127DA3AA935",
+            ),
+            (
+                &[0, 0, 1, 1],
+                "69BC22BFA5D106306E48A20679DE1D7389386124D07571D0D872686028C26A3E",
+            ),
+        ];
+        for (indices, expected) in cases {
+            let list: Vec<_> = indices.iter().map(|&i| pks[i]).collect();
+            for reduction in [Reduction::Sequential, Reduction::Parallel] {
+                let key_agg = KeyAgg::new(&list, reduction);
+                assert_eq!(key_agg.key.x().to_vec(), from_hex(expected));
+            }
+        }
+    }
+}
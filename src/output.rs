@@ -0,0 +1,225 @@
+use super::hdr::IntervalLog;
+use super::report::{csv_header, csv_record, CsvReport};
+use clap::ValueEnum;
+use csv::Writer;
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+//file formats results can be written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    //one row per run, every statistic of every measurement
+    Csv,
+    //array of full reports
+    Json,
+    //hdrhistogram interval log of the latencies of every operation
+    Hdr,
+}
+
+//writes the reports of one scheme to every requested format
+pub struct ReportWriter<R> {
+    csv: Option<Writer<File>>,
+    //the csv header is taken from the first report
+    rows: usize,
+    json: Option<(PathBuf, Vec<R>)>,
+    hdr: Option<IntervalLog>,
+}
+
+impl<R: CsvReport + Serialize> ReportWriter<R> {
+    //creates `<dir>/<stem>.<ext>` for every format
+    pub fn create(dir: &Path, stem: &str, formats: &[OutputFormat]) -> Self {
+        let csv = formats.contains(&OutputFormat::Csv).then(|| {
+            let file = File::create(dir.join(format!("{}.csv", stem))).unwrap();
+            Writer::from_writer(file)
+        });
+        let json = formats
+            .contains(&OutputFormat::Json)
+            .then(|| (dir.join(format!("{}.json", stem)), Vec::new()));
+        let hdr = formats
+            .contains(&OutputFormat::Hdr)
+            .then(|| IntervalLog::create(&dir.join(format!("{}.hlog", stem)), stem));
+
+        ReportWriter {
+            csv,
+            rows: 0,
+            json,
+            hdr,
+        }
+    }
+
+    pub fn write(&mut self, report: R) {
+        if let Some(wtr) = &mut self.csv {
+            let cells = report.csv_cells();
+            if self.rows == 0 {
+                //setting coloumn name
+                wtr.write_record(csv_header(&cells)).unwrap();
+            }
+            wtr.write_record(csv_record(&cells)).unwrap();
+            wtr.flush().unwrap();
+            self.rows += 1;
+        }
+        if let Some((_, reports)) = &mut self.json {
+            reports.push(report);
+        }
+    }
+
+    //histograms of the report of the run that started at `run_start`
+    pub fn write_histograms(
+        &mut self,
+        run_start: Instant,
+        nodes: usize,
+        message_size: usize,
+        histograms: &[&(String, Histogram<u64>)],
+    ) {
+        if let Some(log) = &mut self.hdr {
+            log.write(run_start, nodes, message_size, histograms);
+        }
+    }
+
+    //json is written as one array once all reports are in
+    pub fn finish(self) {
+        if let Some((path, reports)) = self.json {
+            let file = File::create(path).unwrap();
+            serde_json::to_writer_pretty(file, &reports).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed25519::{benchmark_ed25519_signs, Ed25519Report};
+    use crate::report::Measurement;
+    use crate::BenchParams;
+    use base64::prelude::{Engine, BASE64_STANDARD};
+    use hdrhistogram::serialization::interval_log::{IntervalLogIterator, LogEntry};
+    use hdrhistogram::serialization::Deserializer;
+    use serde_json::Value;
+    use std::fs;
+    use std::time::Duration;
+
+    //two small runs with every kind of cell filled: timings, the parallel
+    //verification and the throughput
+    fn reports() -> Vec<Ed25519Report> {
+        let params = BenchParams {
+            warmup: 0,
+            iterations: 3,
+            seed: 11,
+            throughput: Some(Duration::from_millis(5)),
+            threads: 2,
+            parallel_verify: true,
+            ..BenchParams::default()
+        };
+        [2, 3]
+            .into_iter()
+            .map(|nodes| benchmark_ed25519_signs(nodes, &params).unwrap())
+            .collect()
+    }
+
+    //the statistics of `measurement` as serialized in the json report
+    fn assert_json_matches(json: &Value, measurement: &Measurement) {
+        //serde_json may parse a float back one ulp off
+        let assert_close = |field: &str, expected: f64| {
+            let parsed = json[field].as_f64().unwrap();
+            assert!(
+                (parsed - expected).abs() <= 1e-12 * expected.abs(),
+                "{}",
+                field
+            );
+        };
+        assert_eq!(json["count"], measurement.stats.count);
+        assert_close("mean", measurement.stats.mean);
+        assert_close("p99", measurement.stats.p99);
+        assert_close("max", measurement.stats.max);
+        assert_eq!(json["unit"], "ms");
+    }
+
+    #[test]
+    fn formats_carry_the_same_reports() {
+        let dir = std::env::temp_dir().join(format!("blsttc_benchmark_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let reports = reports();
+
+        let formats = [OutputFormat::Csv, OutputFormat::Json, OutputFormat::Hdr];
+        let mut wtr = ReportWriter::create(&dir, "ed25519", &formats);
+        for report in &reports {
+            wtr.write_histograms(
+                Instant::now(),
+                report.run.nodes,
+                report.run.message_size,
+                &report.histograms(),
+            );
+            wtr.write(report.clone());
+        }
+        wtr.finish();
+
+        //the header is written once, from the first report, and every row
+        //has a cell for each of its columns
+        let mut csv = csv::Reader::from_path(dir.join("ed25519.csv")).unwrap();
+        let header: Vec<String> = csv.headers().unwrap().iter().map(String::from).collect();
+        assert_eq!(header, csv_header(&reports[0].csv_cells()));
+        let records: Vec<_> = csv.records().map(Result::unwrap).collect();
+        assert_eq!(records.len(), reports.len());
+        for (record, report) in records.iter().zip(&reports) {
+            let cells = report.csv_cells();
+            assert_eq!(csv_header(&cells), header);
+            assert_eq!(record.iter().collect::<Vec<_>>(), csv_record(&cells));
+            let column = |name: &str| &record[header.iter().position(|h| h == name).unwrap()];
+            assert_eq!(column("nodes"), report.run.nodes.to_string());
+            let verify = report.ed25519.verify.stats;
+            assert_eq!(column("verify_single_sign"), format!("{:.4}", verify.mean));
+            assert_eq!(
+                column("verify_single_sign_p99"),
+                format!("{:.4}", verify.p99)
+            );
+            assert_eq!(column("verify_single_sign_count"), verify.count.to_string());
+        }
+
+        let json: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("ed25519.json")).unwrap()).unwrap();
+        let json = json.as_array().unwrap();
+        assert_eq!(json.len(), reports.len());
+        for (json, report) in json.iter().zip(&reports) {
+            assert_eq!(json["nodes"], report.run.nodes);
+            assert_json_matches(&json["ed25519"]["sign"], &report.ed25519.sign);
+            assert_json_matches(&json["ed25519"]["verify"], &report.ed25519.verify);
+            assert_json_matches(&json["ed25519"]["verify_n"], &report.ed25519.verify_n);
+        }
+
+        //the histograms hold the same samples, in nanoseconds
+        let log = fs::read(dir.join("ed25519.hlog")).unwrap();
+        let intervals: Vec<_> = IntervalLogIterator::new(&log)
+            .map(Result::unwrap)
+            .filter_map(|entry| match entry {
+                LogEntry::Interval(interval) => Some(interval),
+                _ => None,
+            })
+            .collect();
+        for report in &reports {
+            let tag = format!(
+                "ed25519_sign/verify/n{}/m{}",
+                report.run.nodes, report.run.message_size
+            );
+            let interval = intervals
+                .iter()
+                .find(|interval| interval.tag().map(|tag| tag.as_str()) == Some(&tag[..]))
+                .unwrap();
+            let bytes = BASE64_STANDARD
+                .decode(interval.encoded_histogram())
+                .unwrap();
+            let histogram: Histogram<u64> =
+                Deserializer::new().deserialize(&mut &bytes[..]).unwrap();
+            let verify = report.ed25519.verify.stats;
+            assert_eq!(histogram.len(), verify.count as u64);
+            let max = (verify.max * 1_000_000.0).round() as u64;
+            assert!(histogram.equivalent(histogram.max(), max));
+            assert!(histogram.equivalent(interval.max() as u64, max));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
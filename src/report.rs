@@ -0,0 +1,509 @@
+use super::stats::{Samples, StatsError, Summary};
+use super::BenchParams;
+use hdrhistogram::Histogram;
+use serde::Serialize;
+use std::fmt;
+
+//unit a measurement is reported in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Unit {
+    #[serde(rename = "ms")]
+    Milliseconds,
+}
+
+impl Unit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Unit::Milliseconds => "ms",
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+//timing of a single operation, summarised over all of its samples
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Measurement {
+    #[serde(flatten)]
+    pub stats: Summary,
+    pub unit: Unit,
+}
+
+impl Measurement {
+    //builds a measurement from timings taken in milliseconds
+    pub fn from_millis(samples: &Samples) -> Result<Self, StatsError> {
+        Ok(Measurement {
+            stats: samples.summary()?,
+            unit: Unit::Milliseconds,
+        })
+    }
+}
+
+//mean with the spread and tail of the samples
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.4} {} (95% ci {:.4}..{:.4}, std dev {:.4}, p99 {:.4}, max {:.4}, {} samples",
+            self.stats.mean,
+            self.unit,
+            self.stats.mean_ci.lower,
+            self.stats.mean_ci.upper,
+            self.stats.std_dev,
+            self.stats.p99,
+            self.stats.max,
+            self.stats.count
+        )?;
+        if let (Some(mild), Some(severe)) = (self.stats.mild_outliers, self.stats.severe_outliers) {
+            write!(f, ", {} outliers", mild + severe)?;
+        }
+        f.write_str(")")
+    }
+}
+
+//verification of n signs on a worker pool of `threads` threads
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct ParallelVerify {
+    pub threads: usize,
+    pub time: Measurement,
+    //mean time on a single thread over the mean time on `threads` threads
+    pub speedup: f64,
+}
+
+//sustained operations per second of an operation run in a closed loop
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Rate {
+    pub single_thread: f64,
+    pub multi_thread: f64,
+}
+
+//throughput of a scheme, for capacity planning
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Throughput {
+    //threads of the multi-threaded rates
+    pub threads: usize,
+    pub sign: Rate,
+    pub verify: Rate,
+    //aggregation (combining, for the threshold scheme) and verification of
+    //the aggregate, for schemes that support it
+    pub aggregate_sign: Option<Rate>,
+    pub verify_aggregate: Option<Rate>,
+}
+
+impl Throughput {
+    //names of the operations returned by `rates`, in the same order
+    pub const OPERATIONS: [&'static str; 4] =
+        ["sign", "verify", "aggregate_sign", "verify_aggregate"];
+
+    pub fn rates(&self) -> [Option<Rate>; 4] {
+        [
+            Some(self.sign),
+            Some(self.verify),
+            self.aggregate_sign,
+            self.verify_aggregate,
+        ]
+    }
+}
+
+//timings produced by `benchmark_scheme` for one scheme; the aggregate
+//fields are only set for schemes that support aggregation
+#[derive(Clone, Debug, Serialize)]
+pub struct SchemeReport {
+    pub keygen: Measurement,
+    //proofs of possession of all nodes and checking them, for schemes
+    //whose keys carry one
+    pub prove_possession: Option<Measurement>,
+    pub verify_possession: Option<Measurement>,
+    //nonce round of all nodes, for schemes that sign in two rounds
+    pub commit_nonces: Option<Measurement>,
+    //hash of the message the scheme signs: hash-to-curve for bls, the
+    //digest for ecdsa
+    pub hash: Option<Measurement>,
+    //whether sign and verify were timed without hashing the message
+    pub prehashed: bool,
+    pub sign: Measurement,
+    pub verify: Measurement,
+    pub verify_fplus1: Measurement,
+    pub verify_n: Measurement,
+    //the same signs verified as one batch, for schemes that support it
+    pub verify_fplus1_batch: Option<Measurement>,
+    pub verify_n_batch: Option<Measurement>,
+    //one entry per pool size, empty unless verified in parallel
+    pub verify_n_parallel: Vec<ParallelVerify>,
+    pub aggregate_pubkey: Option<Measurement>,
+    pub aggregate_sign: Option<Measurement>,
+    //the same aggregates reduced across threads, for schemes whose
+    //aggregate is a plain sum
+    pub aggregate_pubkey_parallel: Option<Measurement>,
+    pub aggregate_sign_parallel: Option<Measurement>,
+    pub verify_aggregate: Option<Measurement>,
+    //pubkey of a node recovered from its sign, for schemes whose signs
+    //carry a recovery id
+    pub recover_pubkey: Option<Measurement>,
+    //time to reject a single invalid sign, by how it was made invalid;
+    //wrong pubkey needs a second node
+    pub reject_wrong_msg: Measurement,
+    pub reject_wrong_pubkey: Option<Measurement>,
+    pub reject_tampered_sign: Option<Measurement>,
+    pub reject_identity_sign: Option<Measurement>,
+    pub reject_forged_proof: Option<Measurement>,
+    //proof of possession of a rogue key
+    pub reject_rogue_key: Option<Measurement>,
+    //only measured in throughput mode
+    pub throughput: Option<Throughput>,
+    //verifications that returned the wrong result; the timings of a run
+    //with failures don't measure the real verification path
+    pub verify_failures: usize,
+    //latencies of every operation, tagged scheme/operation, for the
+    //interval log
+    #[serde(skip)]
+    pub histograms: Vec<(String, Histogram<u64>)>,
+}
+
+impl SchemeReport {
+    pub fn is_valid(&self) -> bool {
+        self.verify_failures == 0
+    }
+
+    //mean time to set up the keys of all nodes, proofs of possession
+    //included
+    pub fn setup(&self) -> f64 {
+        [
+            Some(self.keygen),
+            self.prove_possession,
+            self.verify_possession,
+        ]
+        .iter()
+        .flatten()
+        .map(|measurement| measurement.stats.mean)
+        .sum()
+    }
+
+    //prints the report in the same layout for every scheme
+    pub fn print(&self, name: &str) {
+        println!("for {} : ", name);
+        println!("time takes to generate all keys: {}", self.keygen);
+        if let Some(prove_possession) = &self.prove_possession {
+            println!(
+                "time takes to prove possession of all keys: {}",
+                prove_possession
+            );
+        }
+        if let Some(verify_possession) = &self.verify_possession {
+            println!(
+                "time takes to verify possession of all keys: {}",
+                verify_possession
+            );
+            println!(
+                "mean time to set up all keys: {:.4} {}",
+                self.setup(),
+                self.keygen.unit
+            );
+        }
+        if let Some(commit_nonces) = &self.commit_nonces {
+            println!(
+                "time takes to commit nonces of all nodes: {}",
+                commit_nonces
+            );
+        }
+        if let Some(hash) = &self.hash {
+            println!("mean time to hash the message: {}", hash);
+        }
+        if self.prehashed {
+            println!("(sign and verify below take the hashed message)");
+        }
+        println!(
+            "mean time to create single {}: {:.4} {}",
+            name, self.sign.stats.mean, self.sign.unit
+        );
+        println!(
+            "median time to create single {}: {:.4} {}",
+            name, self.sign.stats.median, self.sign.unit
+        );
+        println!(
+            "mean time to verify single {}: {:.4} {}",
+            name, self.verify.stats.mean, self.verify.unit
+        );
+        println!(
+            "median time to verify single {}: {:.4} {}",
+            name, self.verify.stats.median, self.verify.unit
+        );
+        println!(
+            "time takes to verify f+1 signs ({}): {}",
+            name, self.verify_fplus1
+        );
+        println!("time takes to verify n signs ({}): {}", name, self.verify_n);
+        if let Some(verify_fplus1_batch) = &self.verify_fplus1_batch {
+            println!(
+                "time takes to batch verify f+1 signs ({}): {}",
+                name, verify_fplus1_batch
+            );
+        }
+        if let Some(verify_n_batch) = &self.verify_n_batch {
+            println!(
+                "time takes to batch verify n signs ({}): {}",
+                name, verify_n_batch
+            );
+        }
+        for parallel in &self.verify_n_parallel {
+            println!(
+                "time takes to verify n signs on {} threads ({}): {}, speedup {:.2}x",
+                parallel.threads, name, parallel.time, parallel.speedup
+            );
+        }
+        if let Some(aggregate_pubkey) = &self.aggregate_pubkey {
+            println!(
+                "time takes to create aggregated pubkey: {}",
+                aggregate_pubkey
+            );
+        }
+        if let Some(aggregate_sign) = &self.aggregate_sign {
+            println!("time takes to create aggregated sign: {}", aggregate_sign);
+        }
+        if let Some(aggregate_pubkey) = &self.aggregate_pubkey_parallel {
+            println!(
+                "time takes to create aggregated pubkey in parallel: {}",
+                aggregate_pubkey
+            );
+        }
+        if let Some(aggregate_sign) = &self.aggregate_sign_parallel {
+            println!(
+                "time takes to create aggregated sign in parallel: {}",
+                aggregate_sign
+            );
+        }
+        if let Some(verify_aggregate) = &self.verify_aggregate {
+            println!("time takes to verify aggregated sign: {}", verify_aggregate);
+        }
+        if let Some(recover_pubkey) = &self.recover_pubkey {
+            println!(
+                "mean time to recover the pubkey from {}: {}",
+                name, recover_pubkey
+            );
+        }
+        println!(
+            "mean time to reject {} with wrong message: {}",
+            name, self.reject_wrong_msg
+        );
+        let rejections = [
+            ("wrong pubkey", &self.reject_wrong_pubkey),
+            ("tampered sign", &self.reject_tampered_sign),
+            ("identity sign", &self.reject_identity_sign),
+            ("forged proof", &self.reject_forged_proof),
+            ("rogue key", &self.reject_rogue_key),
+        ];
+        for (invalid, measurement) in rejections {
+            if let Some(measurement) = measurement {
+                println!(
+                    "mean time to reject {} with {}: {}",
+                    name, invalid, measurement
+                );
+            }
+        }
+        if let Some(throughput) = &self.throughput {
+            for (operation, rate) in Throughput::OPERATIONS.iter().zip(throughput.rates()) {
+                if let Some(rate) = rate {
+                    println!(
+                        "throughput of {} ({}): {:.1} ops/sec on 1 thread, {:.1} ops/sec on {} threads",
+                        operation, name, rate.single_thread, rate.multi_thread, throughput.threads
+                    );
+                }
+            }
+        }
+        if !self.is_valid() {
+            println!(
+                "INVALID RUN: {} verifications of {} returned the wrong result",
+                self.verify_failures, name
+            );
+        }
+        println!();
+    }
+}
+
+//settings of one run of the sweep, at the head of every report
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct RunInfo {
+    pub nodes: usize,
+    pub message_size: usize,
+    //seed the keys of this run were derived from
+    pub seed: u64,
+    //sign and verify were timed on the hashed payload (the session, for
+    //frost); schemes that sign the payload as is ignore it
+    pub prehashed: bool,
+}
+
+impl RunInfo {
+    pub fn new(nodes: usize, params: &BenchParams) -> Self {
+        RunInfo {
+            nodes,
+            message_size: params.message.len(),
+            seed: params.seed,
+            prehashed: params.prehashed,
+        }
+    }
+
+    //first cells of every row
+    pub fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        vec![
+            ("nodes", CsvCell::value(self.nodes)),
+            ("message_size", CsvCell::value(self.message_size)),
+            ("seed", CsvCell::value(self.seed)),
+            ("prehashed", CsvCell::value(self.prehashed)),
+        ]
+    }
+}
+
+//a report that can be written as one row of a csv file
+pub trait CsvReport {
+    //named cells of the row, in column order
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)>;
+    //reports of the schemes that were run, e.g. one per group
+    fn schemes(&self) -> Vec<&SchemeReport>;
+
+    //failed verifications over all schemes of the report
+    fn verify_failures(&self) -> usize {
+        self.schemes()
+            .iter()
+            .map(|report| report.verify_failures)
+            .sum()
+    }
+
+    //latency histograms of all schemes of the report, with their tags
+    fn histograms(&self) -> Vec<&(String, Histogram<u64>)> {
+        self.schemes()
+            .into_iter()
+            .flat_map(|report| &report.histograms)
+            .collect()
+    }
+}
+
+//a cell of a csv row; a timing fills one column per statistic
+pub enum CsvCell {
+    Value(String),
+    //left empty when the scheme has no such operation
+    Timing(Option<Measurement>),
+    //left empty when the throughput wasn't measured
+    Throughput(Option<Throughput>),
+    //mean time and speedup per pool size; the pool sizes are the same for
+    //every run of a sweep, so the columns line up with the header
+    Parallel(Vec<ParallelVerify>),
+}
+
+impl CsvCell {
+    pub fn value(value: impl fmt::Display) -> Self {
+        CsvCell::Value(value.to_string())
+    }
+}
+
+//column names of a row; the mean of a timing keeps the bare name, the other
+//statistics get it as prefix, e.g. `verify_single_sign_p99`
+pub fn csv_header(cells: &[(&'static str, CsvCell)]) -> Vec<String> {
+    let mut header = Vec::new();
+    for (name, cell) in cells {
+        match cell {
+            CsvCell::Value(_) => header.push(name.to_string()),
+            CsvCell::Timing(_) => {
+                header.push(name.to_string());
+                for stat in Summary::STATS[1..].iter().chain(&Summary::COUNTS) {
+                    header.push(format!("{}_{}", name, stat));
+                }
+            }
+            //e.g. `verify_n_signs_parallel_g1_4_threads_speedup`
+            CsvCell::Parallel(parallel) => {
+                for run in parallel {
+                    header.push(format!("{}_{}_threads", name, run.threads));
+                    header.push(format!("{}_{}_threads_speedup", name, run.threads));
+                }
+            }
+            //e.g. `throughput_g1_verify_multi_thread`
+            CsvCell::Throughput(_) => {
+                header.push(format!("{}_threads", name));
+                for operation in Throughput::OPERATIONS {
+                    header.push(format!("{}_{}_single_thread", name, operation));
+                    header.push(format!("{}_{}_multi_thread", name, operation));
+                }
+            }
+        }
+    }
+
+    header
+}
+
+pub fn csv_record(cells: &[(&'static str, CsvCell)]) -> Vec<String> {
+    let mut record = Vec::new();
+    for (_, cell) in cells {
+        match cell {
+            CsvCell::Value(value) => record.push(value.clone()),
+            CsvCell::Timing(Some(measurement)) => {
+                //statistics that weren't computed are left empty
+                for value in measurement.stats.values() {
+                    record.push(value.map(|v| format!("{:.4}", v)).unwrap_or_default());
+                }
+                for count in measurement.stats.counts() {
+                    record.push(count.map(|c| c.to_string()).unwrap_or_default());
+                }
+            }
+            CsvCell::Timing(None) => {
+                let columns = Summary::STATS.len() + Summary::COUNTS.len();
+                record.extend((0..columns).map(|_| String::new()));
+            }
+            CsvCell::Parallel(parallel) => {
+                for run in parallel {
+                    record.push(format!("{:.4}", run.time.stats.mean));
+                    record.push(format!("{:.2}", run.speedup));
+                }
+            }
+            CsvCell::Throughput(Some(throughput)) => {
+                record.push(throughput.threads.to_string());
+                for rate in throughput.rates() {
+                    let (single, multi) = match rate {
+                        Some(rate) => (
+                            format!("{:.1}", rate.single_thread),
+                            format!("{:.1}", rate.multi_thread),
+                        ),
+                        None => Default::default(),
+                    };
+                    record.push(single);
+                    record.push(multi);
+                }
+            }
+            CsvCell::Throughput(None) => {
+                let columns = 1 + 2 * Throughput::OPERATIONS.len();
+                record.extend((0..columns).map(|_| String::new()));
+            }
+        }
+    }
+
+    record
+}
+
+//formats the failure count of a scheme that may not have been run
+pub fn csv_failures_opt(report: &Option<SchemeReport>) -> String {
+    report
+        .as_ref()
+        .map(|report| report.verify_failures.to_string())
+        .unwrap_or_default()
+}
+
+//last cells of the reports run in both groups: throughput and failures
+pub fn group_outcome_cells(
+    g1: &Option<SchemeReport>,
+    g2: &Option<SchemeReport>,
+) -> [(&'static str, CsvCell); 4] {
+    [
+        (
+            "throughput_g1",
+            CsvCell::Throughput(g1.as_ref().and_then(|g1| g1.throughput)),
+        ),
+        (
+            "throughput_g2",
+            CsvCell::Throughput(g2.as_ref().and_then(|g2| g2.throughput)),
+        ),
+        ("verify_failures_g1", CsvCell::Value(csv_failures_opt(g1))),
+        ("verify_failures_g2", CsvCell::Value(csv_failures_opt(g2))),
+    ]
+}
@@ -0,0 +1,53 @@
+use blsttc::rand::rngs::OsRng;
+use blsttc::rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::cell::RefCell;
+
+//every random value of the crate (keys, nonces) is drawn from this rng, so
+//a run can be repeated bit-for-bit from its seed
+thread_local! {
+    static RNG: RefCell<ChaCha20Rng> = RefCell::new(ChaCha20Rng::from_entropy());
+}
+
+//draws a fresh seed from the os, for runs that don't ask for one
+pub fn random_seed() -> u64 {
+    OsRng.next_u64()
+}
+
+//stream of the payloads, apart from the streams of the keys which are
+//picked by node count
+const MESSAGE_STREAM: u64 = u64::MAX;
+
+//payload of `size` random bytes; it only depends on the seed, so every
+//scheme and node count of a run signs the same payload
+pub fn random_message(seed: u64, size: usize) -> Vec<u8> {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    rng.set_stream(MESSAGE_STREAM);
+    let mut message = vec![0u8; size];
+    rng.fill_bytes(&mut message);
+
+    message
+}
+
+//restarts the rng of the current thread from `seed`; `stream` picks an
+//independent sequence so that e.g. every node count gets its own keys
+pub fn reseed(seed: u64, stream: u64) {
+    RNG.with(|rng| {
+        let mut reseeded = ChaCha20Rng::seed_from_u64(seed);
+        reseeded.set_stream(stream);
+        *rng.borrow_mut() = reseeded;
+    });
+}
+
+pub fn with_rng<T>(f: impl FnOnce(&mut ChaCha20Rng) -> T) -> T {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+//runs `f` on a copy of the rng of the current thread and puts the rng back
+//afterwards, so what `f` draws doesn't move the values drawn after it
+pub fn rewind_after<T>(f: impl FnOnce() -> T) -> T {
+    let saved = with_rng(|rng| rng.clone());
+    let result = f();
+    RNG.with(|rng| *rng.borrow_mut() = saved);
+    result
+}
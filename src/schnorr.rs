@@ -0,0 +1,145 @@
+use super::report::{CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::{benchmark_scheme, BenchError, BenchParams, SignatureScheme};
+use k256::schnorr;
+use k256::schnorr::signature::hazmat::{PrehashSigner as _, PrehashVerifier as _};
+use k256::schnorr::signature::{Signer as _, Verifier as _};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+//sha-256 prefixed with the hash of `tag` twice, as bip-340 and musig2 use to
+//separate the hashes of different purposes
+pub fn tagged_hash(tag: &[u8]) -> Sha256 {
+    let tag = Sha256::digest(tag);
+    Sha256::new().chain_update(tag).chain_update(tag)
+}
+
+//bip-340 schnorr over secp256k1; the message signed is its sha-256 digest
+pub struct Bip340Schnorr;
+
+impl SignatureScheme for Bip340Schnorr {
+    type PublicKey = schnorr::VerifyingKey;
+    type SecretKey = schnorr::SigningKey;
+    type Signature = schnorr::Signature;
+    type AggregatePublicKey = ();
+    type AggregateSignature = ();
+    //sha-256 digest of the message
+    type MessageHash = [u8; 32];
+
+    fn name(&self) -> &'static str {
+        "bip-340 schnorr sign"
+    }
+
+    fn keygen(&mut self, nodes: usize) -> Vec<(schnorr::VerifyingKey, schnorr::SigningKey)> {
+        (0..nodes)
+            .map(|_| {
+                let sk = with_rng(schnorr::SigningKey::random);
+                (*sk.verifying_key(), sk)
+            })
+            .collect()
+    }
+
+    //the nonce is derived from the key and message, with no auxiliary
+    //randomness
+    fn sign(&self, sk: &schnorr::SigningKey, msg: &[u8]) -> schnorr::Signature {
+        sk.sign(msg)
+    }
+
+    fn verify(&self, pk: &schnorr::VerifyingKey, sig: &schnorr::Signature, msg: &[u8]) -> bool {
+        pk.verify(msg, sig).is_ok()
+    }
+
+    fn hash_message(&self, msg: &[u8]) -> Option<[u8; 32]> {
+        Some(Sha256::digest(msg).into())
+    }
+
+    fn sign_hashed(&self, sk: &schnorr::SigningKey, hash: &[u8; 32]) -> Option<schnorr::Signature> {
+        Some(sk.sign_prehash(hash).unwrap())
+    }
+
+    fn verify_hashed(
+        &self,
+        pk: &schnorr::VerifyingKey,
+        sig: &schnorr::Signature,
+        hash: &[u8; 32],
+    ) -> Option<bool> {
+        Some(pk.verify_prehash(hash, sig).is_ok())
+    }
+
+    //flips a bit of s
+    fn tampered_signature(&self, sig: &schnorr::Signature) -> Option<schnorr::Signature> {
+        let mut bytes = sig.to_bytes();
+        bytes[63] ^= 1;
+        schnorr::Signature::try_from(&bytes[..]).ok()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SchnorrReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub schnorr: SchemeReport,
+}
+
+impl CsvReport for SchnorrReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            ("hash_sha256", CsvCell::Timing(self.schnorr.hash)),
+            (
+                "creation_single_sign",
+                CsvCell::Timing(Some(self.schnorr.sign)),
+            ),
+            (
+                "verify_single_sign",
+                CsvCell::Timing(Some(self.schnorr.verify)),
+            ),
+            (
+                "verify_fplus1_signs",
+                CsvCell::Timing(Some(self.schnorr.verify_fplus1)),
+            ),
+            (
+                "verify_n_signs",
+                CsvCell::Timing(Some(self.schnorr.verify_n)),
+            ),
+            (
+                "verify_n_signs_parallel",
+                CsvCell::Parallel(self.schnorr.verify_n_parallel.clone()),
+            ),
+            (
+                "reject_wrong_msg",
+                CsvCell::Timing(Some(self.schnorr.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey",
+                CsvCell::Timing(self.schnorr.reject_wrong_pubkey),
+            ),
+            (
+                "reject_tampered_sig",
+                CsvCell::Timing(self.schnorr.reject_tampered_sign),
+            ),
+            ("throughput", CsvCell::Throughput(self.schnorr.throughput)),
+            (
+                "verify_failures",
+                CsvCell::value(self.schnorr.verify_failures),
+            ),
+        ]);
+        cells
+    }
+
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        vec![&self.schnorr]
+    }
+}
+
+pub fn benchmark_schnorr_signs(
+    nodes: usize,
+    params: &BenchParams,
+) -> Result<SchnorrReport, BenchError> {
+    let schnorr = benchmark_scheme(&mut Bip340Schnorr, nodes, params)?;
+
+    Ok(SchnorrReport {
+        run: RunInfo::new(nodes, params),
+        schnorr,
+    })
+}
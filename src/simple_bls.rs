@@ -1,234 +1,242 @@
-use super::stats::{calculate_mean, calculate_median};
-use blsttc::{hash_g1, hash_g2, PublicKeyG1, PublicKeyG2, SecretKey};
-use std::time::Instant;
-
-pub fn benchmark_normal_bls(nodes: usize) -> (f64, f64, f64, f64, f64, f64) {
-    // for normal sign-verify
-    let mut keypairs: Vec<(PublicKeyG1, PublicKeyG2, SecretKey)> = Vec::new();
-    let msg = "hello, this is benchmark".as_bytes();
-    let mut signs_g1 = Vec::new();
-    let mut signs_g2 = Vec::new();
-
-    for _ in 0..nodes {
-        let sk = SecretKey::random();
-        let pkg1 = sk.public_key_g1();
-        let pkg2 = sk.public_key_g2();
-        keypairs.push((pkg1, pkg2, sk));
+use super::forgery::{tampered_g1, tampered_g2};
+use super::report::{group_outcome_cells, CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::{benchmark_scheme, BenchError, BenchParams, Group, SignatureScheme};
+use blsttc::group::prime::PrimeCurveAffine;
+use blsttc::rand::Rng;
+use blsttc::{
+    hash_g1, hash_g2, G1Affine, G2Affine, PublicKeyG1, PublicKeyG2, SecretKey, SignatureG1,
+    SignatureG2,
+};
+use serde::Serialize;
+
+//plain bls signatures in g1, verified with a pubkey in g2
+pub struct SimpleBlsG1;
+
+impl SignatureScheme for SimpleBlsG1 {
+    type PublicKey = PublicKeyG2;
+    type SecretKey = SecretKey;
+    type Signature = SignatureG1;
+    type AggregatePublicKey = ();
+    type AggregateSignature = ();
+    type MessageHash = G1Affine;
+
+    fn name(&self) -> &'static str {
+        "bls sign in g1"
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for creating single sign in g1
-    let mut time_to_create_normal_sign_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        let sign = keypairs[i].2.sign_g1(hash_g1(msg));
-
-        let end_time = Instant::now();
-
-        signs_g1.push(sign);
-
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_normal_sign_g1.push(elapsed_time.as_secs_f64());
+    fn keygen(&mut self, nodes: usize) -> Vec<(PublicKeyG2, SecretKey)> {
+        (0..nodes)
+            .map(|_| {
+                let sk: SecretKey = with_rng(|rng| rng.gen());
+                (sk.public_key_g2(), sk)
+            })
+            .collect()
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for creating single sign in g2
-    let mut time_to_create_normal_sign_g2 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        let sign = keypairs[i].2.sign_g2(hash_g2(msg));
-
-        let end_time = Instant::now();
-
-        signs_g2.push(sign);
-
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_normal_sign_g2.push(elapsed_time.as_secs_f64());
+    fn sign(&self, sk: &SecretKey, msg: &[u8]) -> SignatureG1 {
+        sk.sign_g1(hash_g1(msg))
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying single sign g1
-    let mut time_to_verify_normal_sign_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        keypairs[i].1.verify(&signs_g1[i], msg);
-
-        let end_time = Instant::now();
-
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_normal_sign_g1.push(elapsed_time.as_secs_f64());
+    fn verify(&self, pk: &PublicKeyG2, sig: &SignatureG1, msg: &[u8]) -> bool {
+        pk.verify(sig, msg)
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying single sign g2
-    let mut time_to_verify_normal_sign_g2 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        keypairs[i].0.verify(&signs_g2[i], msg);
-
-        let end_time = Instant::now();
-
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_normal_sign_g2.push(elapsed_time.as_secs_f64());
+    fn hash_message(&self, msg: &[u8]) -> Option<G1Affine> {
+        Some(hash_g1(msg))
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for f+1 sign g1
-    let mut time_to_verify_fplus1_independent_signs_g1 = 0.0;
-    let n = (nodes - 1) / 3 + 1;
-
-    let start_time = Instant::now();
-
-    for i in 0..n {
-        keypairs[i].1.verify(&signs_g1[i], msg);
+    fn sign_hashed(&self, sk: &SecretKey, hash: &G1Affine) -> Option<SignatureG1> {
+        Some(sk.sign_g1(*hash))
     }
 
-    let end_time = Instant::now();
-
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_fplus1_independent_signs_g1 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for f+1 sign g2
-    let mut time_to_verify_fplus1_independent_signs_g2 = 0.0;
-    let n = (nodes - 1) / 3 + 1;
-
-    let start_time = Instant::now();
+    fn verify_hashed(&self, pk: &PublicKeyG2, sig: &SignatureG1, hash: &G1Affine) -> Option<bool> {
+        Some(pk.verify_g1(sig, *hash))
+    }
 
-    for i in 0..n {
-        keypairs[i].0.verify(&signs_g2[i], msg);
+    fn tampered_signature(&self, sig: &SignatureG1) -> Option<SignatureG1> {
+        Some(SignatureG1(tampered_g1(&sig.0)))
     }
 
-    let end_time = Instant::now();
+    fn identity_signature(&self, _sig: &SignatureG1) -> Option<SignatureG1> {
+        Some(SignatureG1(G1Affine::identity()))
+    }
+}
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_fplus1_independent_signs_g2 = elapsed_time.as_secs_f64();
+//plain bls signatures in g2, verified with a pubkey in g1
+pub struct SimpleBlsG2;
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+impl SignatureScheme for SimpleBlsG2 {
+    type PublicKey = PublicKeyG1;
+    type SecretKey = SecretKey;
+    type Signature = SignatureG2;
+    type AggregatePublicKey = ();
+    type AggregateSignature = ();
+    type MessageHash = G2Affine;
 
-    //for all sign g1
-    let mut time_to_verify_all_independent_signs_g1 = 0.0;
+    fn name(&self) -> &'static str {
+        "bls sign in g2"
+    }
 
-    let start_time = Instant::now();
+    fn keygen(&mut self, nodes: usize) -> Vec<(PublicKeyG1, SecretKey)> {
+        (0..nodes)
+            .map(|_| {
+                let sk: SecretKey = with_rng(|rng| rng.gen());
+                (sk.public_key_g1(), sk)
+            })
+            .collect()
+    }
 
-    for i in 0..nodes {
-        keypairs[i].1.verify(&signs_g1[i], msg);
+    fn sign(&self, sk: &SecretKey, msg: &[u8]) -> SignatureG2 {
+        sk.sign_g2(hash_g2(msg))
     }
 
-    let end_time = Instant::now();
+    fn verify(&self, pk: &PublicKeyG1, sig: &SignatureG2, msg: &[u8]) -> bool {
+        pk.verify(sig, msg)
+    }
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs_g1 = elapsed_time.as_secs_f64();
+    fn hash_message(&self, msg: &[u8]) -> Option<G2Affine> {
+        Some(hash_g2(msg))
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn sign_hashed(&self, sk: &SecretKey, hash: &G2Affine) -> Option<SignatureG2> {
+        Some(sk.sign_g2(*hash))
+    }
 
-    //for all sign g2
-    let mut time_to_verify_all_independent_signs_g2 = 0.0;
+    fn verify_hashed(&self, pk: &PublicKeyG1, sig: &SignatureG2, hash: &G2Affine) -> Option<bool> {
+        Some(pk.verify_g2(sig, *hash))
+    }
 
-    let start_time = Instant::now();
+    fn tampered_signature(&self, sig: &SignatureG2) -> Option<SignatureG2> {
+        Some(SignatureG2(tampered_g2(&sig.0)))
+    }
 
-    for i in 0..nodes {
-        keypairs[i].0.verify(&signs_g2[i], msg);
+    fn identity_signature(&self, _sig: &SignatureG2) -> Option<SignatureG2> {
+        Some(SignatureG2(G2Affine::identity()))
     }
+}
 
-    let end_time = Instant::now();
+#[derive(Clone, Debug, Serialize)]
+pub struct SimpleBlsReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub g1: Option<SchemeReport>,
+    pub g2: Option<SchemeReport>,
+}
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs_g2 = elapsed_time.as_secs_f64();
+impl CsvReport for SimpleBlsReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            (
+                "hash_to_curve_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.hash)),
+            ),
+            (
+                "hash_to_curve_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.hash)),
+            ),
+            (
+                "creation_single_sign_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.sign)),
+            ),
+            (
+                "creation_single_sign_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.sign)),
+            ),
+            (
+                "verify_single_sign_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify)),
+            ),
+            (
+                "verify_single_sign_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify)),
+            ),
+            (
+                "verify_n_signs_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify_n)),
+            ),
+            (
+                "verify_n_signs_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify_n)),
+            ),
+            (
+                "verify_n_signs_parallel_g1",
+                CsvCell::Parallel(
+                    self.g1
+                        .iter()
+                        .flat_map(|g1| g1.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+            (
+                "verify_n_signs_parallel_g2",
+                CsvCell::Parallel(
+                    self.g2
+                        .iter()
+                        .flat_map(|g2| g2.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+            (
+                "reject_wrong_msg_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_msg_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.reject_wrong_pubkey)),
+            ),
+            (
+                "reject_wrong_pubkey_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.reject_wrong_pubkey)),
+            ),
+            (
+                "reject_tampered_sig_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.reject_tampered_sign)),
+            ),
+            (
+                "reject_tampered_sig_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.reject_tampered_sign)),
+            ),
+            (
+                "reject_identity_sig_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.reject_identity_sign)),
+            ),
+            (
+                "reject_identity_sig_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.reject_identity_sign)),
+            ),
+        ]);
+        cells.extend(group_outcome_cells(&self.g1, &self.g2));
+        cells
+    }
 
-    let mean_single_sign_creation_g1 = calculate_mean(&time_to_create_normal_sign_g1) * 1000.0;
-    let median_single_sign_creation_g1 =
-        calculate_median(&mut time_to_create_normal_sign_g1) * 1000.0;
-    let mean_single_sign_verify_g1 = calculate_mean(&time_to_verify_normal_sign_g1) * 1000.0;
-    let median_single_sign_verify_g1 =
-        calculate_median(&mut time_to_verify_normal_sign_g1) * 1000.0;
-    let fplusq_sign_verify_g1 = time_to_verify_fplus1_independent_signs_g1 * 1000.0;
-    let n_sign_verify_g1 = time_to_verify_all_independent_signs_g1 * 1000.0;
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        [&self.g1, &self.g2].into_iter().flatten().collect()
+    }
+}
 
-    let mean_single_sign_creation_g2 = calculate_mean(&time_to_create_normal_sign_g2) * 1000.0;
-    let median_single_sign_creation_g2 =
-        calculate_median(&mut time_to_create_normal_sign_g2) * 1000.0;
-    let mean_single_sign_verify_g2 = calculate_mean(&time_to_verify_normal_sign_g2) * 1000.0;
-    let median_single_sign_verify_g2 =
-        calculate_median(&mut time_to_verify_normal_sign_g2) * 1000.0;
-    let fplusq_sign_verify_g2 = time_to_verify_fplus1_independent_signs_g2 * 1000.0;
-    let n_sign_verify_g2 = time_to_verify_all_independent_signs_g2 * 1000.0;
-
-    //OUTPUT
-    println!("for sign in g1 : ");
-    println!(
-        "mean time to create single bls sign in g1: {:.4} ms",
-        mean_single_sign_creation_g1
-    );
-    println!(
-        "median time to create single bls signin g1: {:.4} ms",
-        median_single_sign_creation_g1
-    );
-    println!(
-        "mean time to verify single bls sign in g1: {:.4} ms",
-        mean_single_sign_verify_g1
-    );
-    println!(
-        "meadian time to verify single bls sign in g1: {:.4} ms",
-        median_single_sign_verify_g1
-    );
-    println!(
-        "time takes to verify f+1 signs in g1: {:.4} ms",
-        fplusq_sign_verify_g1
-    );
-    println!(
-        "time takes to verify n signs in g1: {:.4} ms",
-        n_sign_verify_g1
-    );
-    println!("");
-
-    println!("for sign in g2 : ");
-    println!(
-        "mean time to create single bls sign in g2: {:.4} ms",
-        mean_single_sign_creation_g2
-    );
-    println!(
-        "median time to create single bls sign in g2: {:.4} ms",
-        median_single_sign_creation_g2
-    );
-    println!(
-        "mean time to verify single bls sign in g2: {:.4} ms",
-        mean_single_sign_verify_g2
-    );
-    println!(
-        "meadian time to verify single bls sign in g2: {:.4} ms",
-        median_single_sign_verify_g2
-    );
-    println!(
-        "time takes to verify f+1 signs in g2: {:.4} ms",
-        fplusq_sign_verify_g2
-    );
-    println!(
-        "time takes to verify n signs in g2: {:.4} ms",
-        n_sign_verify_g2
-    );
-    println!("");
-
-    (
-        mean_single_sign_creation_g1,
-        mean_single_sign_creation_g2,
-        mean_single_sign_verify_g1,
-        mean_single_sign_verify_g2,
-        n_sign_verify_g1,
-        n_sign_verify_g2,
-    )
+pub fn benchmark_normal_bls(
+    nodes: usize,
+    groups: &[Group],
+    params: &BenchParams,
+) -> Result<SimpleBlsReport, BenchError> {
+    let g1 = groups
+        .contains(&Group::G1)
+        .then(|| benchmark_scheme(&mut SimpleBlsG1, nodes, params))
+        .transpose()?;
+    let g2 = groups
+        .contains(&Group::G2)
+        .then(|| benchmark_scheme(&mut SimpleBlsG2, nodes, params))
+        .transpose()?;
+
+    Ok(SimpleBlsReport {
+        run: RunInfo::new(nodes, params),
+        g1,
+        g2,
+    })
 }
@@ -1,14 +1,756 @@
-pub fn calculate_mean(numbers: &[f64]) -> f64 {
+use super::hdr::new_histogram;
+use blsttc::rand::{Rng, SeedableRng};
+use hdrhistogram::Histogram;
+use rand_chacha::ChaCha20Rng;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+//why statistics can't be computed over a set of numbers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsError {
+    Empty,
+    //a number is nan or infinite
+    NonFinite,
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::Empty => f.write_str("no samples"),
+            StatsError::NonFinite => f.write_str("samples contain nan or infinity"),
+        }
+    }
+}
+
+impl Error for StatsError {}
+
+//numbers every statistic can be computed over
+fn check(numbers: &[f64]) -> Result<(), StatsError> {
+    if numbers.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    if !numbers.iter().all(|n| n.is_finite()) {
+        return Err(StatsError::NonFinite);
+    }
+
+    Ok(())
+}
+
+pub fn calculate_mean(numbers: &[f64]) -> Result<f64, StatsError> {
+    check(numbers)?;
     let sum: f64 = numbers.iter().sum();
-    sum / (numbers.len() as f64)
+    Ok(sum / (numbers.len() as f64))
+}
+
+pub fn calculate_median(numbers: &mut [f64]) -> Result<f64, StatsError> {
+    check(numbers)?;
+    numbers.sort_by(f64::total_cmp);
+    calculate_percentile(numbers, 50.0)
+}
+
+//sample standard deviation (n - 1), 0 for a single sample
+pub fn calculate_std_dev(numbers: &[f64], mean: f64) -> Result<f64, StatsError> {
+    check(numbers)?;
+    if numbers.len() < 2 {
+        return Ok(0.0);
+    }
+    let squares: f64 = numbers.iter().map(|n| (n - mean).powi(2)).sum();
+    Ok((squares / (numbers.len() - 1) as f64).sqrt())
+}
+
+//`p`th percentile (clamped to 0..=100) of sorted numbers, interpolated
+//linearly between the two closest ranks
+pub fn calculate_percentile(sorted: &[f64], p: f64) -> Result<f64, StatsError> {
+    check(sorted)?;
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Ok(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+//mean of the sorted numbers left after dropping `fraction` (clamped below
+//a half) of them at each end
+pub fn calculate_trimmed_mean(sorted: &[f64], fraction: f64) -> Result<f64, StatsError> {
+    check(sorted)?;
+    let trim = (sorted.len() as f64 * fraction.clamp(0.0, 0.49)) as usize;
+    calculate_mean(&sorted[trim..sorted.len() - trim])
+}
+
+//median absolute deviation from the median
+pub fn calculate_mad(numbers: &[f64], median: f64) -> Result<f64, StatsError> {
+    let mut deviations: Vec<f64> = numbers.iter().map(|n| (n - median).abs()).collect();
+    calculate_median(&mut deviations)
+}
+
+//range an estimate lies in at some confidence
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Interval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+//percentile bootstrap: recomputes `estimate` over `resamples` resamples
+//(with replacement) of the numbers and takes the central `confidence`
+//share of the results
+pub fn bootstrap_ci(
+    numbers: &[f64],
+    estimate: impl Fn(&mut [f64]) -> Result<f64, StatsError>,
+    resamples: usize,
+    confidence: f64,
+    rng: &mut impl Rng,
+) -> Result<Interval, StatsError> {
+    check(numbers)?;
+    let mut resample = vec![0.0; numbers.len()];
+    let mut estimates = (0..resamples)
+        .map(|_| {
+            for value in resample.iter_mut() {
+                *value = numbers[rng.gen_range(0..numbers.len())];
+            }
+            estimate(&mut resample)
+        })
+        .collect::<Result<Vec<f64>, StatsError>>()?;
+    estimates.sort_by(f64::total_cmp);
+
+    let tail = (1.0 - confidence) / 2.0 * 100.0;
+    Ok(Interval {
+        lower: calculate_percentile(&estimates, tail)?,
+        upper: calculate_percentile(&estimates, 100.0 - tail)?,
+    })
+}
+
+//how far a sample lies outside the bulk of the distribution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outlier {
+    //beyond 1.5 iqr from the quartiles
+    Mild,
+    //beyond 3 iqr from the quartiles
+    Severe,
+}
+
+//tukey fences around the interquartile range of a set of samples
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fences {
+    pub q1: f64,
+    pub q3: f64,
+}
+
+impl Fences {
+    //quartiles of fewer than 4 samples say nothing about the spread, so the
+    //fences are put at the ends and no sample is an outlier
+    pub fn from_sorted(sorted: &[f64]) -> Result<Self, StatsError> {
+        check(sorted)?;
+        if sorted.len() < 4 {
+            return Ok(Fences {
+                q1: sorted[0],
+                q3: sorted[sorted.len() - 1],
+            });
+        }
+        Ok(Fences {
+            q1: calculate_percentile(sorted, 25.0)?,
+            q3: calculate_percentile(sorted, 75.0)?,
+        })
+    }
+
+    pub fn iqr(&self) -> f64 {
+        self.q3 - self.q1
+    }
+
+    pub fn classify(&self, sample: f64) -> Option<Outlier> {
+        let distance = (self.q1 - sample).max(sample - self.q3);
+        if distance > 3.0 * self.iqr() {
+            Some(Outlier::Severe)
+        } else if distance > 1.5 * self.iqr() {
+            Some(Outlier::Mild)
+        } else {
+            None
+        }
+    }
+}
+
+//labels every sample, in the order given
+pub fn label_outliers(samples: &[f64]) -> Result<Vec<Option<Outlier>>, StatsError> {
+    check(samples)?;
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let fences = Fences::from_sorted(&sorted)?;
+    Ok(samples.iter().map(|&s| fences.classify(s)).collect())
+}
+
+//mean and variance updated one number at a time (welford's algorithm),
+//without keeping the numbers
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Welford {
+    count: usize,
+    mean: f64,
+    //sum of the squared distances from the mean
+    m2: f64,
+}
+
+impl Welford {
+    pub fn push(&mut self, number: f64) {
+        self.count += 1;
+        let delta = number - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (number - self.mean);
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> Result<f64, StatsError> {
+        if self.count == 0 {
+            return Err(StatsError::Empty);
+        }
+        Ok(self.mean)
+    }
+
+    //sample standard deviation (n - 1), 0 for a single number, as
+    //`calculate_std_dev`
+    pub fn std_dev(&self) -> Result<f64, StatsError> {
+        match self.count {
+            0 => Err(StatsError::Empty),
+            1 => Ok(0.0),
+            n => Ok((self.m2 / (n - 1) as f64).sqrt()),
+        }
+    }
+}
+
+//estimate of the `p`th quantile updated one number at a time in constant
+//memory, with the p-square algorithm of jain and chlamtac: five markers
+//track the minimum, the maximum, the quantile and the quantiles halfway
+//to either end, and are moved along a parabola as numbers come in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct P2Quantile {
+    //quantile as a fraction, 0..=1
+    p: f64,
+    count: usize,
+    //marker heights; the first numbers as is until there are five
+    heights: [f64; 5],
+    //actual and desired marker positions, 1-based ranks
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    //`p`th percentile, clamped to 0..=100 as in `calculate_percentile`
+    pub fn new(p: f64) -> Self {
+        let p = p.clamp(0.0, 100.0) / 100.0;
+        P2Quantile {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    pub fn push(&mut self, number: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = number;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(f64::total_cmp);
+            }
+            return;
+        }
+        self.count += 1;
+
+        //cell the number falls in, stretching the ends to cover it
+        let cell = if number < self.heights[0] {
+            self.heights[0] = number;
+            0
+        } else if number >= self.heights[4] {
+            self.heights[4] = number;
+            3
+        } else {
+            (0..4).find(|&i| number < self.heights[i + 1]).unwrap_or(3)
+        };
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        //moves the middle markers that are off their desired position by a
+        //rank or more, as long as they don't run into their neighbours
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+            let room_up = self.positions[i + 1] - self.positions[i];
+            let room_down = self.positions[i - 1] - self.positions[i];
+            if (offset >= 1.0 && room_up > 1.0) || (offset <= -1.0 && room_down < -1.0) {
+                let step = offset.signum();
+                let height = self.parabolic(i, step);
+                self.heights[i] = if self.heights[i - 1] < height && height < self.heights[i + 1] {
+                    height
+                } else {
+                    self.linear(i, step)
+                };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + step / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + step) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - step) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let j = if step > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i]
+            + step * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+
+    //exact while the markers still hold every number
+    pub fn estimate(&self) -> Result<f64, StatsError> {
+        if self.count <= 5 {
+            let mut first = self.heights[..self.count].to_vec();
+            first.sort_by(f64::total_cmp);
+            return calculate_percentile(&first, self.p * 100.0);
+        }
+
+        Ok(self.heights[2])
+    }
+}
+
+//timings of one operation in milliseconds as they are taken: always
+//summarised on the fly and recorded in a histogram, and kept as well when
+//the statistics that need every sample are wanted
+#[derive(Clone, Debug, PartialEq)]
+pub struct Samples {
+    welford: Welford,
+    min: f64,
+    max: f64,
+    non_finite: bool,
+    //q1, median, q3, p90, p95, p99
+    quantiles: [P2Quantile; 6],
+    //in nanoseconds
+    histogram: Histogram<u64>,
+    raw: Option<Vec<f64>>,
 }
 
-pub fn calculate_median(numbers: &mut [f64]) -> f64 {
-    numbers.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    let mid = numbers.len() / 2;
-    if numbers.len() % 2 == 0 {
-        (numbers[mid - 1] + numbers[mid]) / 2.0
-    } else {
-        numbers[mid]
+impl Samples {
+    pub fn new(keep_raw: bool) -> Self {
+        Samples {
+            welford: Welford::default(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            non_finite: false,
+            quantiles: [25.0, 50.0, 75.0, 90.0, 95.0, 99.0].map(P2Quantile::new),
+            histogram: new_histogram(),
+            raw: keep_raw.then(Vec::new),
+        }
+    }
+
+    pub fn push(&mut self, sample: f64) {
+        if let Some(raw) = &mut self.raw {
+            raw.push(sample);
+        }
+        if !sample.is_finite() {
+            self.non_finite = true;
+            return;
+        }
+        self.welford.push(sample);
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+        for quantile in &mut self.quantiles {
+            quantile.push(sample);
+        }
+        self.histogram
+            .record((sample.max(0.0) * 1_000_000.0).round() as u64)
+            .expect("the histogram grows to any value");
+    }
+
+    pub fn histogram(&self) -> &Histogram<u64> {
+        &self.histogram
+    }
+
+    //exact statistics when the samples were kept, estimates otherwise
+    pub fn summary(&self) -> Result<Summary, StatsError> {
+        if let Some(raw) = &self.raw {
+            return Summary::from_samples(raw);
+        }
+        if self.non_finite {
+            return Err(StatsError::NonFinite);
+        }
+
+        let mean = self.welford.mean()?;
+        let std_dev = self.welford.std_dev()?;
+        //normal approximation, as there are no samples left to resample
+        let margin = Summary::NORMAL_QUANTILE * std_dev / (self.welford.count() as f64).sqrt();
+        let [q1, median, q3, p90, p95, p99] = self.quantiles.map(|q| q.estimate());
+        let (q1, q3) = (q1?, q3?);
+
+        Ok(Summary {
+            count: self.welford.count(),
+            mean,
+            mean_ci: Interval {
+                lower: mean - margin,
+                upper: mean + margin,
+            },
+            median: median?,
+            median_ci: None,
+            min: self.min,
+            max: self.max,
+            std_dev,
+            p90: p90?,
+            p95: p95?,
+            p99: p99?,
+            iqr: q3 - q1,
+            trimmed_mean: None,
+            mad: None,
+            mild_outliers: None,
+            severe_outliers: None,
+        })
+    }
+}
+
+//distribution of the samples of one operation; the statistics that need
+//every sample are `None` when the samples were only summarised on the fly
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub mean_ci: Interval,
+    pub median: f64,
+    pub median_ci: Option<Interval>,
+    pub min: f64,
+    pub max: f64,
+    pub std_dev: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    //interquartile range, p75 - p25
+    pub iqr: f64,
+    //robust estimates, not moved by a few preempted samples: the mean of
+    //the middle 80% and the median absolute deviation
+    pub trimmed_mean: Option<f64>,
+    pub mad: Option<f64>,
+    pub mild_outliers: Option<usize>,
+    pub severe_outliers: Option<usize>,
+}
+
+impl Summary {
+    //share of the samples dropped at each end for the trimmed mean
+    pub const TRIM: f64 = 0.1;
+    //bootstrap of the confidence intervals of mean and median; the
+    //resampling is seeded, so the same samples always give the same interval
+    pub const CONFIDENCE: f64 = 0.95;
+    pub const RESAMPLES: usize = 1000;
+    const BOOTSTRAP_SEED: u64 = 0;
+    //two-sided quantile of the standard normal at `CONFIDENCE`, for the
+    //confidence interval of the mean of streamed samples
+    const NORMAL_QUANTILE: f64 = 1.959964;
+
+    //names of the statistics returned by `values`, in the same order
+    pub const STATS: [&'static str; 15] = [
+        "mean",
+        "mean_ci_lower",
+        "mean_ci_upper",
+        "median",
+        "median_ci_lower",
+        "median_ci_upper",
+        "min",
+        "max",
+        "std_dev",
+        "p90",
+        "p95",
+        "p99",
+        "iqr",
+        "trimmed_mean",
+        "mad",
+    ];
+
+    //names of the counts returned by `counts`, in the same order
+    pub const COUNTS: [&'static str; 3] = ["count", "mild_outliers", "severe_outliers"];
+
+    pub fn from_samples(samples: &[f64]) -> Result<Self, StatsError> {
+        let mut sorted = samples.to_vec();
+        let median = calculate_median(&mut sorted)?;
+        let mean = calculate_mean(&sorted)?;
+        let fences = Fences::from_sorted(&sorted)?;
+        let outliers: Vec<_> = sorted.iter().filter_map(|&s| fences.classify(s)).collect();
+        let count_of = |outlier| outliers.iter().filter(|&&o| o == outlier).count();
+        let mut rng = ChaCha20Rng::seed_from_u64(Self::BOOTSTRAP_SEED);
+        let mut bootstrap = |estimate: fn(&mut [f64]) -> Result<f64, StatsError>| {
+            bootstrap_ci(
+                &sorted,
+                estimate,
+                Self::RESAMPLES,
+                Self::CONFIDENCE,
+                &mut rng,
+            )
+        };
+        let mean_ci = bootstrap(|resample| calculate_mean(resample))?;
+        let median_ci = bootstrap(calculate_median)?;
+
+        Ok(Summary {
+            count: sorted.len(),
+            mean,
+            mean_ci,
+            median,
+            median_ci: Some(median_ci),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            std_dev: calculate_std_dev(&sorted, mean)?,
+            p90: calculate_percentile(&sorted, 90.0)?,
+            p95: calculate_percentile(&sorted, 95.0)?,
+            p99: calculate_percentile(&sorted, 99.0)?,
+            iqr: calculate_percentile(&sorted, 75.0)? - calculate_percentile(&sorted, 25.0)?,
+            trimmed_mean: Some(calculate_trimmed_mean(&sorted, Self::TRIM)?),
+            mad: Some(calculate_mad(&sorted, median)?),
+            mild_outliers: Some(count_of(Outlier::Mild)),
+            severe_outliers: Some(count_of(Outlier::Severe)),
+        })
+    }
+
+    pub fn values(&self) -> [Option<f64>; 15] {
+        [
+            Some(self.mean),
+            Some(self.mean_ci.lower),
+            Some(self.mean_ci.upper),
+            Some(self.median),
+            self.median_ci.map(|ci| ci.lower),
+            self.median_ci.map(|ci| ci.upper),
+            Some(self.min),
+            Some(self.max),
+            Some(self.std_dev),
+            Some(self.p90),
+            Some(self.p95),
+            Some(self.p99),
+            Some(self.iqr),
+            self.trimmed_mean,
+            self.mad,
+        ]
+    }
+
+    pub fn counts(&self) -> [Option<usize>; 3] {
+        [Some(self.count), self.mild_outliers, self.severe_outliers]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //1 to 20, with a mild and a severe outlier planted above
+    fn with_outliers() -> Vec<f64> {
+        let mut samples: Vec<f64> = (1..=20).map(f64::from).collect();
+        samples.extend([40.0, 80.0]);
+        samples
+    }
+
+    #[test]
+    fn fences_classify_planted_outliers() {
+        let samples = with_outliers();
+        let fences = Fences::from_sorted(&samples).unwrap();
+        assert_eq!(fences.q1, 6.25);
+        assert_eq!(fences.q3, 16.75);
+        assert_eq!(fences.iqr(), 10.5);
+
+        //the mild fence is at 32.5 and the severe one at 48.25
+        assert_eq!(fences.classify(32.5), None);
+        assert_eq!(fences.classify(32.6), Some(Outlier::Mild));
+        assert_eq!(fences.classify(48.25), Some(Outlier::Mild));
+        assert_eq!(fences.classify(48.3), Some(Outlier::Severe));
+        assert_eq!(fences.classify(-9.6), Some(Outlier::Mild));
+
+        let labels = label_outliers(&samples).unwrap();
+        assert!(labels[..20].iter().all(Option::is_none));
+        assert_eq!(labels[20..], [Some(Outlier::Mild), Some(Outlier::Severe)]);
+    }
+
+    #[test]
+    fn fences_of_fewer_than_four_samples_flag_nothing() {
+        let samples = [1.0, 2.0, 100.0];
+        let fences = Fences::from_sorted(&samples).unwrap();
+        assert_eq!((fences.q1, fences.q3), (1.0, 100.0));
+        assert_eq!(label_outliers(&samples).unwrap(), [None; 3]);
+        assert_eq!(label_outliers(&[5.0]).unwrap(), [None]);
+
+        let summary = Summary::from_samples(&samples).unwrap();
+        assert_eq!(summary.mild_outliers, Some(0));
+        assert_eq!(summary.severe_outliers, Some(0));
+        //still the spread of the quartiles
+        assert_eq!(summary.iqr, 49.5);
+    }
+
+    #[test]
+    fn trimmed_mean_drops_a_tenth_at_each_end() {
+        let mut sorted: Vec<f64> = (1..=9).map(f64::from).collect();
+        sorted.push(1000.0);
+        //1 and 1000 are dropped, the mean of 2 to 9 is left
+        assert_eq!(calculate_trimmed_mean(&sorted, 0.1).unwrap(), 5.5);
+        assert_eq!(calculate_trimmed_mean(&sorted, 0.0).unwrap(), 104.5);
+        //at most just under a half is dropped at each end
+        assert_eq!(calculate_trimmed_mean(&sorted, 0.9).unwrap(), 5.5);
+
+        let summary = Summary::from_samples(&with_outliers()).unwrap();
+        let middle = calculate_mean(&(3..=20).map(f64::from).collect::<Vec<_>>()).unwrap();
+        assert_eq!(summary.trimmed_mean, Some(middle));
+    }
+
+    #[test]
+    fn mad_is_the_median_distance_from_the_median() {
+        let numbers = [1.0, 1.0, 2.0, 2.0, 4.0, 6.0, 9.0];
+        assert_eq!(calculate_mad(&numbers, 2.0).unwrap(), 1.0);
+        assert_eq!(calculate_mad(&[3.0; 5], 3.0).unwrap(), 0.0);
+        assert_eq!(calculate_mad(&[], 0.0), Err(StatsError::Empty));
+    }
+
+    #[test]
+    fn bootstrap_ci_is_reproducible_from_its_seed() {
+        let numbers = with_outliers();
+        let mean = |resample: &mut [f64]| calculate_mean(resample);
+        let interval = |seed| {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            bootstrap_ci(&numbers, mean, 1000, 0.95, &mut rng).unwrap()
+        };
+
+        let ci = interval(7);
+        assert_eq!(ci, interval(7));
+        let estimate = calculate_mean(&numbers).unwrap();
+        assert!(ci.lower <= estimate && estimate <= ci.upper);
+        assert!(ci.lower < ci.upper);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let ci = bootstrap_ci(&numbers, calculate_median, 1000, 0.95, &mut rng).unwrap();
+        let estimate = calculate_median(&mut numbers.clone()).unwrap();
+        assert!(ci.lower <= estimate && estimate <= ci.upper);
+
+        //the summaries are bootstrapped from a fixed seed
+        assert_eq!(
+            Summary::from_samples(&numbers),
+            Summary::from_samples(&numbers)
+        );
+    }
+
+    #[test]
+    fn bootstrap_ci_of_constant_numbers_has_no_width() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let ci = bootstrap_ci(&[2.5; 30], calculate_median, 100, 0.95, &mut rng).unwrap();
+        assert_eq!(
+            ci,
+            Interval {
+                lower: 2.5,
+                upper: 2.5
+            }
+        );
+        assert_eq!(
+            bootstrap_ci(&[], calculate_median, 100, 0.95, &mut rng),
+            Err(StatsError::Empty)
+        );
+    }
+
+    //skewed like timings: exponential with a floor, from a fixed seed
+    fn timings(count: usize) -> Vec<f64> {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        (0..count)
+            .map(|_| 1.0 - (1.0 - rng.gen::<f64>()).ln())
+            .collect()
+    }
+
+    fn assert_close(estimate: f64, exact: f64, tolerance: f64) {
+        assert!(
+            (estimate - exact).abs() <= tolerance * exact.abs(),
+            "{} is not within {} of {}",
+            estimate,
+            tolerance,
+            exact
+        );
+    }
+
+    #[test]
+    fn p2_tracks_the_exact_percentiles() {
+        let numbers = timings(10_000);
+        let mut sorted = numbers.clone();
+        sorted.sort_by(f64::total_cmp);
+        for p in [25.0, 50.0, 75.0, 90.0, 95.0, 99.0] {
+            let mut quantile = P2Quantile::new(p);
+            for &n in &numbers {
+                quantile.push(n);
+            }
+            let exact = calculate_percentile(&sorted, p).unwrap();
+            assert_close(quantile.estimate().unwrap(), exact, 0.02);
+        }
+    }
+
+    #[test]
+    fn p2_is_exact_until_the_markers_fill() {
+        let mut quantile = P2Quantile::new(25.0);
+        assert_eq!(quantile.estimate(), Err(StatsError::Empty));
+        for n in [5.0, 1.0, 4.0] {
+            quantile.push(n);
+        }
+        assert_eq!(quantile.estimate().unwrap(), 2.5);
+        for n in [2.0, 3.0] {
+            quantile.push(n);
+        }
+        assert_eq!(quantile.estimate().unwrap(), 2.0);
+
+        //from the sixth number on the markers move
+        quantile.push(0.0);
+        let estimate = quantile.estimate().unwrap();
+        assert!((0.0..=5.0).contains(&estimate));
+    }
+
+    #[test]
+    fn welford_matches_the_two_pass_result() {
+        let numbers = timings(1000);
+        let mut welford = Welford::default();
+        assert_eq!(welford.mean(), Err(StatsError::Empty));
+        for &n in &numbers {
+            welford.push(n);
+        }
+        let mean = calculate_mean(&numbers).unwrap();
+        assert_eq!(welford.count(), numbers.len());
+        assert_close(welford.mean().unwrap(), mean, 1e-12);
+        assert_close(
+            welford.std_dev().unwrap(),
+            calculate_std_dev(&numbers, mean).unwrap(),
+            1e-12,
+        );
+
+        let mut single = Welford::default();
+        single.push(3.0);
+        assert_eq!(single.std_dev(), Ok(0.0));
+    }
+
+    #[test]
+    fn streaming_summary_approximates_the_raw_one() {
+        let (mut streamed, mut kept) = (Samples::new(false), Samples::new(true));
+        for n in timings(2000) {
+            streamed.push(n);
+            kept.push(n);
+        }
+        let (streamed, kept) = (streamed.summary().unwrap(), kept.summary().unwrap());
+
+        assert_eq!(streamed.count, kept.count);
+        assert_eq!((streamed.min, streamed.max), (kept.min, kept.max));
+        assert_close(streamed.mean, kept.mean, 1e-12);
+        assert_close(streamed.std_dev, kept.std_dev, 1e-12);
+        for (estimate, exact) in [
+            (streamed.median, kept.median),
+            (streamed.p90, kept.p90),
+            (streamed.p95, kept.p95),
+            (streamed.p99, kept.p99),
+            (streamed.iqr, kept.iqr),
+        ] {
+            assert_close(estimate, exact, 0.05);
+        }
+        //normal approximation against the bootstrap
+        assert_close(streamed.mean_ci.lower, kept.mean_ci.lower, 0.01);
+        assert_close(streamed.mean_ci.upper, kept.mean_ci.upper, 0.01);
+
+        assert_eq!(streamed.median_ci, None);
+        assert_eq!(streamed.trimmed_mean, None);
+        assert_eq!(streamed.mad, None);
+        assert_eq!(streamed.counts(), [Some(2000), None, None]);
     }
 }
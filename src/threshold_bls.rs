@@ -1,292 +1,351 @@
-use super::stats::{calculate_mean, calculate_median};
+use super::forgery::{tampered_g1, tampered_g2};
+use super::report::{group_outcome_cells, CsvCell, CsvReport, RunInfo, SchemeReport};
+use super::rng::with_rng;
+use super::{benchmark_scheme, BenchError, BenchParams, Group, SignatureScheme};
+use blsttc::group::prime::PrimeCurveAffine;
 use blsttc::{
-    PublicKeyShareG1, PublicKeyShareG2, SecretKeySet, SecretKeyShare, SignatureShareG1,
+    hash_g1, hash_g2, G1Affine, G2Affine, PublicKeySet, PublicKeySetG2, PublicKeyShareG1,
+    PublicKeyShareG2, SecretKeySet, SecretKeyShare, SignatureG1, SignatureG2, SignatureShareG1,
     SignatureShareG2,
 };
-use std::time::Instant;
+use serde::Serialize;
 
-pub fn benchmark_threshold_bls(nodes: usize) -> (f64, f64, f64, f64, f64, f64, f64, f64, f64, f64) {
-    let threshold = (nodes - 1) / 3;
-
-    let mut keypairs: Vec<(PublicKeyShareG1, PublicKeyShareG2, SecretKeyShare)> = Vec::new();
-    let msg = "hello, this is benchmark".as_bytes();
-    let mut signs_g1 = Vec::new();
-    let mut signs_g2 = Vec::new();
-
-    let mut rng = blsttc::rand::rngs::OsRng;
+//generates a secret key set of the given threshold and hands one share to
+//every node
+pub fn deal_shares(threshold: usize, nodes: usize) -> (SecretKeySet, Vec<SecretKeyShare>) {
     // Generate a set of secret key shares
-    let sk_set = SecretKeySet::random(threshold, &mut rng);
-    // Get the corresponding public key set
-    let pk_set_g1 = sk_set.public_keys();
-    let pk_set_g2 = sk_set.public_keys_g2();
-
-    for node in 0..nodes {
-        let sk_share = sk_set.secret_key_share(node);
-        let pk_share_g1 = pk_set_g1.public_key_share(node);
-        let pk_share_g2 = pk_set_g2.public_key_share(node);
-
-        keypairs.push((pk_share_g1, pk_share_g2, sk_share));
-    }
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    let sk_set = with_rng(|rng| SecretKeySet::random(threshold, rng));
+    let sk_shares = (0..nodes)
+        .map(|node| sk_set.secret_key_share(node))
+        .collect();
 
-    //for creating sign share in g1
-    let mut time_to_create_sign_share_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        let sign = keypairs[i].2.sign_g1(msg);
-
-        let end_time = Instant::now();
+    (sk_set, sk_shares)
+}
 
-        signs_g1.push(sign);
+//threshold bls sign shares in g1, combined and verified against the
+//master pubkey in g2
+pub struct ThresholdBlsG1 {
+    pub threshold: usize,
+    pk_set: Option<PublicKeySetG2>,
+}
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_sign_share_g1.push(elapsed_time.as_secs_f64());
+impl ThresholdBlsG1 {
+    pub fn new(threshold: usize) -> Self {
+        ThresholdBlsG1 {
+            threshold,
+            pk_set: None,
+        }
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for creating sign share in g2
-    let mut time_to_create_sign_share_g2 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
-
-        let sign = keypairs[i].2.sign_g2(msg);
-
-        let end_time = Instant::now();
-
-        signs_g2.push(sign);
-
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_create_sign_share_g2.push(elapsed_time.as_secs_f64());
+    fn pk_set(&self) -> &PublicKeySetG2 {
+        self.pk_set.as_ref().expect("keygen must run first")
     }
+}
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+impl SignatureScheme for ThresholdBlsG1 {
+    type PublicKey = PublicKeyShareG2;
+    type SecretKey = SecretKeyShare;
+    type Signature = SignatureShareG1;
+    type AggregatePublicKey = ();
+    type AggregateSignature = SignatureG1;
+    type MessageHash = G1Affine;
 
-    //for verifying single g1 sign share
-    let mut time_to_verify_single_sign_share_g1 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
+    fn name(&self) -> &'static str {
+        "bls sign share in g1"
+    }
 
-        keypairs[i].1.verify(&signs_g1[i], msg);
+    fn keygen(&mut self, nodes: usize) -> Vec<(PublicKeyShareG2, SecretKeyShare)> {
+        let (sk_set, sk_shares) = deal_shares(self.threshold, nodes);
+        // Get the corresponding public key set
+        let pk_set = sk_set.public_keys_g2();
+        let keypairs = sk_shares
+            .into_iter()
+            .enumerate()
+            .map(|(node, sk_share)| (pk_set.public_key_share(node), sk_share))
+            .collect();
+        self.pk_set = Some(pk_set);
+
+        keypairs
+    }
 
-        let end_time = Instant::now();
+    fn sign(&self, sk: &SecretKeyShare, msg: &[u8]) -> SignatureShareG1 {
+        sk.sign_g1(msg)
+    }
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_single_sign_share_g1.push(elapsed_time.as_secs_f64());
+    fn verify(&self, pk: &PublicKeyShareG2, sig: &SignatureShareG1, msg: &[u8]) -> bool {
+        pk.verify(sig, msg)
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn hash_message(&self, msg: &[u8]) -> Option<G1Affine> {
+        Some(hash_g1(msg))
+    }
 
-    //for verifying single g2 sign share
-    let mut time_to_verify_single_sign_share_g2 = Vec::new();
-    for i in 0..nodes {
-        let start_time = Instant::now();
+    fn sign_hashed(&self, sk: &SecretKeyShare, hash: &G1Affine) -> Option<SignatureShareG1> {
+        Some(SignatureShareG1(sk.0.sign_g1(*hash)))
+    }
 
-        keypairs[i].0.verify(&signs_g2[i], msg);
+    fn verify_hashed(
+        &self,
+        pk: &PublicKeyShareG2,
+        sig: &SignatureShareG1,
+        hash: &G1Affine,
+    ) -> Option<bool> {
+        Some(pk.0.verify_g1(&sig.0, *hash))
+    }
 
-        let end_time = Instant::now();
+    fn tampered_signature(&self, sig: &SignatureShareG1) -> Option<SignatureShareG1> {
+        Some(SignatureShareG1(SignatureG1(tampered_g1(&sig.0 .0))))
+    }
 
-        // Calculate elapsed time
-        let elapsed_time = end_time.duration_since(start_time);
-        time_to_verify_single_sign_share_g2.push(elapsed_time.as_secs_f64());
+    fn identity_signature(&self, _sig: &SignatureShareG1) -> Option<SignatureShareG1> {
+        Some(SignatureShareG1(SignatureG1(G1Affine::identity())))
     }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn aggregate_signatures(&self, sigs: &[(usize, SignatureShareG1)]) -> Option<SignatureG1> {
+        Some(
+            self.pk_set()
+                .combine_signatures(sigs.iter().cloned())
+                .unwrap(),
+        )
+    }
 
-    //for all sign share in g1
-    let mut time_to_verify_all_independent_signs_g1 = 0.0;
+    fn verify_aggregate(&self, _agg_pk: Option<&()>, agg_sig: &SignatureG1, msg: &[u8]) -> bool {
+        self.pk_set().public_key().verify(agg_sig, msg)
+    }
+}
 
-    let start_time = Instant::now();
+//threshold bls sign shares in g2, combined and verified against the
+//master pubkey in g1
+pub struct ThresholdBlsG2 {
+    pub threshold: usize,
+    pk_set: Option<PublicKeySet>,
+}
 
-    for i in 0..nodes {
-        keypairs[i].1.verify(&signs_g1[i], msg);
+impl ThresholdBlsG2 {
+    pub fn new(threshold: usize) -> Self {
+        ThresholdBlsG2 {
+            threshold,
+            pk_set: None,
+        }
     }
 
-    let end_time = Instant::now();
+    fn pk_set(&self) -> &PublicKeySet {
+        self.pk_set.as_ref().expect("keygen must run first")
+    }
+}
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs_g1 = elapsed_time.as_secs_f64();
+impl SignatureScheme for ThresholdBlsG2 {
+    type PublicKey = PublicKeyShareG1;
+    type SecretKey = SecretKeyShare;
+    type Signature = SignatureShareG2;
+    type AggregatePublicKey = ();
+    type AggregateSignature = SignatureG2;
+    type MessageHash = G2Affine;
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn name(&self) -> &'static str {
+        "bls sign share in g2"
+    }
 
-    //for all sign share in g2
-    let mut time_to_verify_all_independent_signs_g2 = 0.0;
+    fn keygen(&mut self, nodes: usize) -> Vec<(PublicKeyShareG1, SecretKeyShare)> {
+        let (sk_set, sk_shares) = deal_shares(self.threshold, nodes);
+        // Get the corresponding public key set
+        let pk_set = sk_set.public_keys();
+        let keypairs = sk_shares
+            .into_iter()
+            .enumerate()
+            .map(|(node, sk_share)| (pk_set.public_key_share(node), sk_share))
+            .collect();
+        self.pk_set = Some(pk_set);
+
+        keypairs
+    }
 
-    let start_time = Instant::now();
+    fn sign(&self, sk: &SecretKeyShare, msg: &[u8]) -> SignatureShareG2 {
+        sk.sign_g2(msg)
+    }
 
-    for i in 0..nodes {
-        keypairs[i].0.verify(&signs_g2[i], msg);
+    fn verify(&self, pk: &PublicKeyShareG1, sig: &SignatureShareG2, msg: &[u8]) -> bool {
+        pk.verify(sig, msg)
     }
 
-    let end_time = Instant::now();
+    fn hash_message(&self, msg: &[u8]) -> Option<G2Affine> {
+        Some(hash_g2(msg))
+    }
 
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_all_independent_signs_g2 = elapsed_time.as_secs_f64();
+    fn sign_hashed(&self, sk: &SecretKeyShare, hash: &G2Affine) -> Option<SignatureShareG2> {
+        Some(SignatureShareG2(sk.0.sign_g2(*hash)))
+    }
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+    fn verify_hashed(
+        &self,
+        pk: &PublicKeyShareG1,
+        sig: &SignatureShareG2,
+        hash: &G2Affine,
+    ) -> Option<bool> {
+        Some(pk.0.verify_g2(&sig.0, *hash))
+    }
 
-    //for creating threshold sign g1
-    let mut time_to_create_threshold_sign_g1 = 0.0;
+    fn tampered_signature(&self, sig: &SignatureShareG2) -> Option<SignatureShareG2> {
+        Some(SignatureShareG2(SignatureG2(tampered_g2(&sig.0 .0))))
+    }
 
-    let mut signatures: Vec<(_, SignatureShareG1)> = Vec::new();
-    for i in 0..nodes {
-        signatures.push((i, signs_g1[i].clone()));
+    fn identity_signature(&self, _sig: &SignatureShareG2) -> Option<SignatureShareG2> {
+        Some(SignatureShareG2(SignatureG2(G2Affine::identity())))
     }
 
-    let start_time = Instant::now();
-    let combined_sign_g1 = pk_set_g2
-        .combine_signatures(signatures.into_iter())
-        .unwrap();
-    let end_time = Instant::now();
+    fn aggregate_signatures(&self, sigs: &[(usize, SignatureShareG2)]) -> Option<SignatureG2> {
+        Some(
+            self.pk_set()
+                .combine_g2_signatures(sigs.iter().cloned())
+                .unwrap(),
+        )
+    }
 
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_threshold_sign_g1 = elapsed_time.as_secs_f64();
+    fn verify_aggregate(&self, _agg_pk: Option<&()>, agg_sig: &SignatureG2, msg: &[u8]) -> bool {
+        self.pk_set().public_key().verify(agg_sig, msg)
+    }
+}
 
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Clone, Debug, Serialize)]
+pub struct ThresholdBlsReport {
+    #[serde(flatten)]
+    pub run: RunInfo,
+    pub threshold: usize,
+    pub g1: Option<SchemeReport>,
+    pub g2: Option<SchemeReport>,
+}
 
-    //for creating threshold sign g2
-    let mut time_to_create_threshold_sign_g2 = 0.0;
+impl CsvReport for ThresholdBlsReport {
+    fn csv_cells(&self) -> Vec<(&'static str, CsvCell)> {
+        let mut cells = self.run.csv_cells();
+        cells.extend([
+            (
+                "hash_to_curve_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.hash)),
+            ),
+            (
+                "hash_to_curve_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.hash)),
+            ),
+            (
+                "creation_single_sign_share_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.sign)),
+            ),
+            (
+                "creation_single_sign_share_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.sign)),
+            ),
+            (
+                "verify_single_sign_share_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify)),
+            ),
+            (
+                "verify_single_sign_share_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify)),
+            ),
+            (
+                "verify_n_signshares_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.verify_n)),
+            ),
+            (
+                "verify_n_signshares_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.verify_n)),
+            ),
+            (
+                "verify_n_signshares_parallel_g1",
+                CsvCell::Parallel(
+                    self.g1
+                        .iter()
+                        .flat_map(|g1| g1.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+            (
+                "verify_n_signshares_parallel_g2",
+                CsvCell::Parallel(
+                    self.g2
+                        .iter()
+                        .flat_map(|g2| g2.verify_n_parallel.clone())
+                        .collect(),
+                ),
+            ),
+            (
+                "creation_threshold_sig_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.aggregate_sign)),
+            ),
+            (
+                "creation_threshold_sig_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.aggregate_sign)),
+            ),
+            (
+                "verify_threshold_sig_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.verify_aggregate)),
+            ),
+            (
+                "verify_threshold_sig_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.verify_aggregate)),
+            ),
+            (
+                "reject_wrong_msg_g1",
+                CsvCell::Timing(self.g1.as_ref().map(|g1| g1.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_msg_g2",
+                CsvCell::Timing(self.g2.as_ref().map(|g2| g2.reject_wrong_msg)),
+            ),
+            (
+                "reject_wrong_pubkey_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.reject_wrong_pubkey)),
+            ),
+            (
+                "reject_wrong_pubkey_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.reject_wrong_pubkey)),
+            ),
+            (
+                "reject_tampered_sig_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.reject_tampered_sign)),
+            ),
+            (
+                "reject_tampered_sig_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.reject_tampered_sign)),
+            ),
+            (
+                "reject_identity_sig_g1",
+                CsvCell::Timing(self.g1.as_ref().and_then(|g1| g1.reject_identity_sign)),
+            ),
+            (
+                "reject_identity_sig_g2",
+                CsvCell::Timing(self.g2.as_ref().and_then(|g2| g2.reject_identity_sign)),
+            ),
+        ]);
+        cells.extend(group_outcome_cells(&self.g1, &self.g2));
+        cells
+    }
 
-    let mut signatures: Vec<(_, SignatureShareG2)> = Vec::new();
-    for i in 0..nodes {
-        signatures.push((i, signs_g2[i].clone()));
+    fn schemes(&self) -> Vec<&SchemeReport> {
+        [&self.g1, &self.g2].into_iter().flatten().collect()
     }
+}
 
-    let start_time = Instant::now();
-    let combined_sign_g2 = pk_set_g1
-        .combine_g2_signatures(signatures.into_iter())
-        .unwrap();
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_create_threshold_sign_g2 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying threshold sign g1
-    let mut time_to_verify_threshold_sign_g1 = 0.0;
-
-    let start_time = Instant::now();
-    pk_set_g2.public_key().verify(&combined_sign_g1, msg);
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_threshold_sign_g1 = elapsed_time.as_secs_f64();
-
-    //////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    //for verifying threshold sign g2
-    let mut time_to_verify_threshold_sign_g2 = 0.0;
-
-    let start_time = Instant::now();
-    pk_set_g1.public_key().verify(&combined_sign_g2, msg);
-    let end_time = Instant::now();
-
-    // Calculate elapsed time
-    let elapsed_time = end_time.duration_since(start_time);
-    time_to_verify_threshold_sign_g2 = elapsed_time.as_secs_f64();
-
-    let mean_single_sign_creation_g1 = calculate_mean(&time_to_create_sign_share_g1) * 1000.0;
-    let median_single_sign_creation_g1 =
-        calculate_median(&mut time_to_create_sign_share_g1) * 1000.0;
-    let mean_single_sign_verify_g1 = calculate_mean(&time_to_verify_single_sign_share_g1) * 1000.0;
-    let median_single_sign_verify_g1 =
-        calculate_median(&mut time_to_verify_single_sign_share_g1) * 1000.0;
-    let n_sign_verify_g1 = time_to_verify_all_independent_signs_g1 * 1000.0;
-    let creation_threshold_sign_g1 = time_to_create_threshold_sign_g1 * 1000.0;
-    let verify_threshold_sign_g1 = time_to_verify_threshold_sign_g1 * 1000.0;
-
-    let mean_single_sign_creation_g2 = calculate_mean(&time_to_create_sign_share_g2) * 1000.0;
-    let median_single_sign_creation_g2 =
-        calculate_median(&mut time_to_create_sign_share_g2) * 1000.0;
-    let mean_single_sign_verify_g2 = calculate_mean(&time_to_verify_single_sign_share_g2) * 1000.0;
-    let median_single_sign_verify_g2 =
-        calculate_median(&mut time_to_verify_single_sign_share_g2) * 1000.0;
-    let n_sign_verify_g2 = time_to_verify_all_independent_signs_g2 * 1000.0;
-    let creation_threshold_sign_g2 = time_to_create_threshold_sign_g2 * 1000.0;
-    let verify_threshold_sign_g2 = time_to_verify_threshold_sign_g2 * 1000.0;
-
-    //OUTPUT
-    println!("for signshare in g1 : ");
-    println!(
-        "mean time to create single  bls sign share in g1: {:.4} ms",
-        mean_single_sign_creation_g1
-    );
-    println!(
-        "median time to create single bls sign share in g1: {:.4} ms",
-        median_single_sign_creation_g1
-    );
-    println!(
-        "mean time to verify single bls sign share in g1: {:.4} ms",
-        mean_single_sign_verify_g1
-    );
-    println!(
-        "meadian time to verify single bls sign share in g1: {:.4} ms",
-        median_single_sign_verify_g1
-    );
-    println!(
-        "time takes to verify n independent signs share in g1: {:.4} ms",
-        n_sign_verify_g1
-    );
-    println!(
-        "time takes to create threshold sign in g1: {:.4} ms",
-        creation_threshold_sign_g1
-    );
-    println!(
-        "time takes to verify threshold sign in g1: {:.4} ms",
-        verify_threshold_sign_g1
-    );
-    println!("");
-
-    println!("for signshare in g2 : ");
-    println!(
-        "mean time to create single  bls sign share in g2: {:.4} ms",
-        mean_single_sign_creation_g2
-    );
-    println!(
-        "median time to create single bls sign share in g2: {:.4} ms",
-        median_single_sign_creation_g2
-    );
-    println!(
-        "mean time to verify single bls sign share in g2: {:.4} ms",
-        mean_single_sign_verify_g2
-    );
-    println!(
-        "meadian time to verify single bls sign share in g2: {:.4} ms",
-        median_single_sign_verify_g2
-    );
-    println!(
-        "time takes to verify n independent signs share in g2: {:.4} ms",
-        n_sign_verify_g2
-    );
-    println!(
-        "time takes to create threshold sign in g2: {:.4} ms",
-        creation_threshold_sign_g2
-    );
-    println!(
-        "time takes to verify threshold sign in g21: {:.4} ms",
-        verify_threshold_sign_g2
-    );
-    println!("");
-
-    (
-        mean_single_sign_creation_g1,
-        mean_single_sign_creation_g2,
-        mean_single_sign_verify_g1,
-        mean_single_sign_verify_g2,
-        n_sign_verify_g1,
-        n_sign_verify_g2,
-        creation_threshold_sign_g1,
-        creation_threshold_sign_g2,
-        verify_threshold_sign_g1,
-        verify_threshold_sign_g2,
-    )
+pub fn benchmark_threshold_bls(
+    nodes: usize,
+    threshold: usize,
+    groups: &[Group],
+    params: &BenchParams,
+) -> Result<ThresholdBlsReport, BenchError> {
+    let g1 = groups
+        .contains(&Group::G1)
+        .then(|| benchmark_scheme(&mut ThresholdBlsG1::new(threshold), nodes, params))
+        .transpose()?;
+    let g2 = groups
+        .contains(&Group::G2)
+        .then(|| benchmark_scheme(&mut ThresholdBlsG2::new(threshold), nodes, params))
+        .transpose()?;
+
+    Ok(ThresholdBlsReport {
+        run: RunInfo::new(nodes, params),
+        threshold,
+        g1,
+        g2,
+    })
 }